@@ -0,0 +1,55 @@
+/*
+ * confiture-core C API
+ *
+ * Build the shared library without the Python frontend:
+ *
+ *     cargo build --release --no-default-features --features ffi
+ *
+ * and link against target/release/libconfiture_core.so (.dylib on macOS).
+ *
+ * Conventions:
+ *   - Input strings are NUL-terminated UTF-8.
+ *   - Output strings are owned by the caller and must be released with
+ *     confiture_string_free(). Any out-pointer may be NULL to discard it.
+ *     Result out-pointers are only written on success.
+ *   - Functions return 0 on success, otherwise a confiture exit code
+ *     (docs/reference/exit-codes.md): 4 for native errors, 5 for invalid
+ *     arguments, 1 if the result cannot be returned as a C string or the
+ *     library panicked (panics never unwind into the caller). On failure
+ *     *error is set to "[CODE] message", e.g. "[SCHEMA_205] ...".
+ */
+
+#ifndef CONFITURE_H
+#define CONFITURE_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Concatenate SQL files, in the given order, into one schema with
+ * "-- File:" headers relative to base_dir (NULL: common parent of files).
+ * *warnings receives newline-separated build notices, or NULL if none.
+ */
+int confiture_build_schema(const char *const *files, size_t n_files,
+                           const char *base_dir, char **schema,
+                           char **warnings, char **error);
+
+/* Hex SHA256 of the files, identical to SchemaBuilder.compute_hash(). */
+int confiture_hash_files(const char *const *files, size_t n_files,
+                         const char *base_dir, char **hash, char **error);
+
+/* Schema-only DDL from a pg_dump custom-format (-Fc) archive. */
+int confiture_extract_dump_schema(const char *path, char **schema,
+                                  char **error);
+
+/* Release a string returned by any function above. NULL is a no-op. */
+void confiture_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CONFITURE_H */
@@ -7,7 +7,9 @@ Performance: Uses Rust extension (_core) when available for 10-50x speedup.
 """
 
 import hashlib
+import os
 import re
+import warnings
 from datetime import datetime
 from pathlib import Path
 from typing import TYPE_CHECKING, Any
@@ -36,6 +38,58 @@ if not TYPE_CHECKING:
     except ImportError:
         pass
 
+# Debug aid: when set, every Rust fast-path call also runs the Python fallback
+# and raises SchemaError if the two disagree.
+_CONSISTENCY_CHECK_ENV = "CONFITURE_NATIVE_CONSISTENCY_CHECK"
+
+
+def _consistency_check_enabled() -> bool:
+    """Return True when the consistency-check env var is set to a truthy value."""
+    value = os.environ.get(_CONSISTENCY_CHECK_ENV, "")
+    return value.strip().lower() in {"1", "true", "yes", "on"}
+
+
+def _is_blank_sql(sql: str) -> bool:
+    """Return True if ``sql`` holds only whitespace and comments.
+
+    Mirrors the Rust ``sql::is_blank``: ``--`` runs to the end of the line,
+    ``/* */`` comments nest, and an unterminated block comment swallows the
+    rest of the input.
+    """
+    i = 0
+    while i < len(sql):
+        if sql[i].isspace():
+            i += 1
+        elif sql.startswith("--", i):
+            newline = sql.find("\n", i)
+            i = len(sql) if newline == -1 else newline + 1
+        elif sql.startswith("/*", i):
+            depth = 0
+            while i < len(sql):
+                if sql.startswith("/*", i):
+                    depth += 1
+                    i += 2
+                elif sql.startswith("*/", i):
+                    depth -= 1
+                    i += 2
+                    if depth == 0:
+                        break
+                else:
+                    i += 1
+        else:
+            return False
+    return True
+
+
+def _first_divergence(expected: str, actual: str) -> int | None:
+    """Return the index of the first differing character, or None if equal."""
+    if expected == actual:
+        return None
+    for i, (a, b) in enumerate(zip(expected, actual, strict=False)):
+        if a != b:
+            return i
+    return min(len(expected), len(actual))
+
 
 class SchemaBuilder:
     """Build PostgreSQL schema from DDL source files
@@ -378,7 +432,7 @@ class SchemaBuilder:
             try:
                 files_and_content[file] = file.read_text(encoding="utf-8")
             except Exception as e:
-                raise SchemaError(f"Error reading {file}: {e}") from e
+                raise SchemaError(f"Error reading {file}: {e}", error_code="SCHEMA_205") from e
 
         # Run validator
         validator = CommentValidator()
@@ -543,6 +597,7 @@ class SchemaBuilder:
         output_path: Path | None = None,
         schema_only: bool = False,
         progress: ProgressManager | None = None,
+        strict: bool = False,
     ) -> str:
         """Build schema by concatenating DDL files
 
@@ -556,12 +611,19 @@ class SchemaBuilder:
             output_path: Optional path to write schema file. If None, only returns content.
             schema_only: If True, exclude seed files. Default False (include all files).
             progress: Optional ProgressManager for displaying build progress
+            strict: If True, fail instead of warning when a file is empty or
+                holds only comments
 
         Returns:
             Generated schema content as string
 
         Raises:
-            SchemaError: If schema build fails
+            SchemaError: If schema build fails, or with code ``SCHEMA_208`` if
+                ``strict`` and a file is empty or comment-only. A
+                ``NativeError`` from the Rust extension is re-raised as
+                ``SchemaError`` with its ``code`` and ``details``
+            confiture._core.NativeWarning: If escalated to an error by a warning
+                filter (e.g. ``-W error::confiture._core.NativeWarning``)
 
         Example:
             >>> builder = SchemaBuilder(env="local")
@@ -615,15 +677,26 @@ class SchemaBuilder:
             try:
                 # Build file content using Rust
                 file_paths = [str(f) for f in files]
-                content: str = _core.build_schema(file_paths)
+                content: str = _core.build_schema(file_paths, str(self.base_dir), strict=strict)
 
                 # Add headers and separators (Python side for flexibility)
                 schema = self._add_headers_and_separators(header, files, content)
+            except Warning:
+                # Escalated NativeWarnings (-W error) are build failures, not
+                # a reason to fall back
+                raise
+            except _core.NativeError as e:
+                raise SchemaError(str(e), error_code=e.code, context=e.details) from e
             except Exception:
                 # Fallback to Python if Rust fails
+                self._report_blank_files(files, strict)
                 schema = self._build_python(header, files, progress=progress)
+            else:
+                if _consistency_check_enabled():
+                    self._check_build_consistency(schema, self._build_python(header, files))
         else:
             # Pure Python implementation (fallback)
+            self._report_blank_files(files, strict)
             schema = self._build_python(header, files, progress=progress)
 
         # Two-pass FK processing: strip FK constraints from CREATE TABLE,
@@ -688,8 +761,9 @@ class SchemaBuilder:
                 # Add file separator (uses configured style)
                 parts.append(self._get_separator_for_file(file))
 
-                # Add file content
-                content = file.read_text(encoding="utf-8")
+                # Add file content (BOM stripped, as on the Rust path, which
+                # also reports it as a _core.NativeWarning)
+                content = file.read_text(encoding="utf-8").removeprefix("\ufeff")
                 parts.append(content)
 
                 # Ensure newline at end
@@ -701,10 +775,47 @@ class SchemaBuilder:
                     progress.update(None, advance=1)
 
             except Exception as e:
-                raise SchemaError(f"Error reading {file}: {e}") from e
+                raise SchemaError(f"Error reading {file}: {e}", error_code="SCHEMA_205") from e
 
         return "".join(parts)
 
+    def _report_blank_files(self, files: list[Path], strict: bool) -> None:
+        """Warn about, or in strict mode reject, empty and comment-only files
+
+        Matches what the Rust extension reports, so a build passes or fails
+        the same way whichever path runs.
+
+        Args:
+            files: SQL files about to be built
+            strict: Raise instead of warning
+
+        Raises:
+            SchemaError: With code ``SCHEMA_208`` if ``strict`` and any file is
+                empty or comment-only
+        """
+        blank = []
+        for file in files:
+            try:
+                content = file.read_text(encoding="utf-8").removeprefix("\ufeff")
+            except Exception as e:
+                raise SchemaError(f"Error reading {file}: {e}", error_code="SCHEMA_205") from e
+            if _is_blank_sql(content):
+                blank.append(str(file))
+
+        if not blank:
+            return
+        if strict:
+            raise SchemaError(
+                f"Schema files are empty or comment-only: {', '.join(blank)}",
+                error_code="SCHEMA_208",
+                context={"files": blank},
+                resolution_hint="Restore the lost content (often a botched merge) or delete the files",
+            )
+        # Same category as the Rust path when the extension is installed
+        category = _core.NativeWarning if HAS_RUST else UserWarning
+        for file in blank:
+            warnings.warn(f"Schema file is empty or comment-only: {file}", category, stacklevel=3)
+
     def _add_headers_and_separators(self, header: str, _files: list[Path], content: str) -> str:
         """Add main header to Rust-built content
 
@@ -722,6 +833,37 @@ class SchemaBuilder:
         # Rust layer now includes file separators, just prepend main header
         return header + content
 
+    def _check_build_consistency(self, rust_schema: str, python_schema: str) -> None:
+        """Raise if the Rust-built schema differs from the Python fallback
+
+        Only called when ``CONFITURE_NATIVE_CONSISTENCY_CHECK`` is set.
+
+        Args:
+            rust_schema: Schema produced via the Rust extension
+            python_schema: Schema produced by ``_build_python``
+
+        Raises:
+            SchemaError: Naming the first differing offset and the source file
+                whose section contains it
+        """
+        offset = _first_divergence(python_schema, rust_schema)
+        if offset is None:
+            return
+
+        # Locate the file section containing the divergence
+        marker = python_schema.rfind("File: ", 0, offset + 1)
+        if marker == -1:
+            location = "<header>"
+        else:
+            location = python_schema[marker + len("File: ") : python_schema.find("\n", marker)]
+
+        raise SchemaError(
+            f"Rust and Python builds diverge at offset {offset} (in {location}): "
+            f"python={python_schema[offset : offset + 40]!r}, "
+            f"rust={rust_schema[offset : offset + 40]!r}",
+            context={"file_path": location, "offset": offset},
+        )
+
     def _is_superuser_file(self, file_path: Path) -> bool:
         """Check if a file belongs to a superuser directory.
 
@@ -861,7 +1003,7 @@ class SchemaBuilder:
             execution_time_ms=elapsed_ms,
         )
 
-    def compute_hash(self) -> str:
+    def compute_hash(self, strict: bool = False) -> str:
         """Compute deterministic SHA256 hash of schema
 
         The hash includes both file paths and content, ensuring that any change
@@ -876,9 +1018,21 @@ class SchemaBuilder:
 
         Performance: Uses Rust extension when available for 30-60x speedup.
 
+        Args:
+            strict: If True, fail instead of warning when a file is empty or
+                holds only comments
+
         Returns:
             SHA256 hexadecimal digest
 
+        Raises:
+            SchemaError: If a file cannot be read, or with code ``SCHEMA_208``
+                if ``strict`` and a file is empty or comment-only. A
+                ``NativeError`` from the Rust extension is re-raised as
+                ``SchemaError`` with its ``code`` and ``details``
+            confiture._core.NativeWarning: If escalated to an error by a warning
+                filter
+
         Example:
             >>> builder = SchemaBuilder(env="local")
             >>> hash1 = builder.compute_hash()
@@ -892,13 +1046,38 @@ class SchemaBuilder:
         if HAS_RUST:
             try:
                 file_paths = [str(f) for f in files]
-                hash_result: str = _core.hash_files(file_paths)
-                return hash_result
+                hash_result: str = _core.hash_files(file_paths, str(self.base_dir), strict=strict)
+            except Warning:
+                raise
+            except _core.NativeError as e:
+                raise SchemaError(str(e), error_code=e.code, context=e.details) from e
             except Exception:
                 # Fallback to Python if Rust fails
                 pass
+            else:
+                if _consistency_check_enabled():
+                    python_hash = self._compute_hash_python(files)
+                    if python_hash != hash_result:
+                        raise SchemaError(
+                            f"Rust and Python schema hashes diverge over {len(files)} files: "
+                            f"python={python_hash}, rust={hash_result}",
+                            context={"python_hash": python_hash, "rust_hash": hash_result},
+                        )
+                return hash_result
 
         # Pure Python implementation (fallback)
+        self._report_blank_files(files, strict)
+        return self._compute_hash_python(files)
+
+    def _compute_hash_python(self, files: list[Path]) -> str:
+        """Pure Python implementation of compute_hash (fallback)
+
+        Args:
+            files: SQL files to hash, in build order
+
+        Returns:
+            SHA256 hexadecimal digest
+        """
         hasher = hashlib.sha256()
 
         for file in files:
@@ -913,7 +1092,9 @@ class SchemaBuilder:
                 hasher.update(content)
                 hasher.update(b"\x00")  # Separator
             except Exception as e:
-                raise SchemaError(f"Error reading {file} for hash: {e}") from e
+                raise SchemaError(
+                    f"Error reading {file} for hash: {e}", error_code="SCHEMA_205"
+                ) from e
 
         return hasher.hexdigest()
 
@@ -927,7 +1108,10 @@ class SchemaBuilder:
             Header string
         """
         timestamp = datetime.now().isoformat()
-        schema_hash = self.compute_hash()
+        # The build reports blank files itself; don't report them twice
+        with warnings.catch_warnings():
+            warnings.simplefilter("ignore", _core.NativeWarning if HAS_RUST else UserWarning)
+            schema_hash = self.compute_hash()
 
         return f"""-- ============================================
 -- PostgreSQL Schema for Confiture
@@ -335,6 +335,55 @@ def _create_global_registry() -> ErrorCodeRegistry:
             exit_code=4,
             resolution_hint="Schema definition has changed; rebuild the schema",
         ),
+        ErrorCodeDefinition(
+            code="SCHEMA_205",
+            message_template="Cannot read schema file: {file}",
+            severity=ErrorSeverity.ERROR,
+            exit_code=4,
+            resolution_hint="Check that the file exists and is readable",
+        ),
+        ErrorCodeDefinition(
+            code="SCHEMA_206",
+            message_template="Invalid native build option: {option}",
+            severity=ErrorSeverity.ERROR,
+            exit_code=4,
+            resolution_hint="Pass a value within the documented range for the option",
+        ),
+        ErrorCodeDefinition(
+            code="SCHEMA_207",
+            message_template="Invalid pg_dump archive: {file}",
+            severity=ErrorSeverity.ERROR,
+            exit_code=4,
+            resolution_hint="Pass a custom-format archive written by pg_dump -Fc (PostgreSQL 9.6-17)",
+        ),
+        ErrorCodeDefinition(
+            code="SCHEMA_208",
+            message_template="Schema files are empty or comment-only: {files}",
+            severity=ErrorSeverity.ERROR,
+            exit_code=4,
+            resolution_hint="Restore the lost content (often a botched merge) or delete the files",
+        ),
+        ErrorCodeDefinition(
+            code="SCHEMA_209",
+            message_template="Cannot write schema package: {file}",
+            severity=ErrorSeverity.ERROR,
+            exit_code=4,
+            resolution_hint="Check that the output directory exists and is writable",
+        ),
+        ErrorCodeDefinition(
+            code="SCHEMA_210",
+            message_template="Schema package failed verification: {file}",
+            severity=ErrorSeverity.ERROR,
+            exit_code=4,
+            resolution_hint="Do not apply it; rebuild the package with package_tree from the source tree",
+        ),
+        ErrorCodeDefinition(
+            code="SCHEMA_211",
+            message_template="Invalid status file: {file}",
+            severity=ErrorSeverity.ERROR,
+            exit_code=4,
+            resolution_hint="Pass the status_path given to the running build",
+        ),
     ]
 
     for code in schema_codes:
@@ -874,6 +923,13 @@ CANONICAL_EXIT_CODES: dict[str, int] = {
     "SCHEMA_202": 4,
     "SCHEMA_203": 4,
     "SCHEMA_204": 4,
+    "SCHEMA_205": 4,
+    "SCHEMA_206": 4,
+    "SCHEMA_207": 4,
+    "SCHEMA_208": 4,
+    "SCHEMA_209": 4,
+    "SCHEMA_210": 4,
+    "SCHEMA_211": 4,
     # SYNC family → 5.
     "SYNC_001": 5,
     "SYNC_300": 5,
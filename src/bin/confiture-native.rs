@@ -0,0 +1,633 @@
+//! confiture-native - standalone CLI over the confiture-core engine
+//!
+//! Runs the native build, hash, lint, diff, verify, packaging and pg_dump
+//! operations without a Python runtime, for minimal deploy and init
+//! containers. Build with:
+//!
+//! ```text
+//! cargo build --release --features cli --bin confiture-native
+//! ```
+//!
+//! Unlike `confiture build`, it does not read environment configs: pass the
+//! SQL files in build order, or directories (expanded recursively to their
+//! `*.sql` files in path order). `lint` and `diff` work on statements as the
+//! native splitter sees them; the Python `confiture lint` rules and
+//! structural schema diff are not reimplemented here.
+
+use confiture_core::{
+    collect_sql_files, compute_hash_with_limits, concat_files_with_progress,
+    concat_files_with_status, find_duplicates, is_blank, read_archive, read_package, schema_ddl,
+    split_statements, verify_chunks, write_chunks, write_package, CoreError, ErrorCode, ReadLimits,
+};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: confiture-native <command> [options]
+
+Commands:
+  build [--base-dir DIR] [--output FILE] [--strict] [--status-file FILE]
+        [--split-dir DIR [--max-statements N] [--max-bytes N]]
+        [READ LIMITS] PATH...     Concatenate SQL files into one schema
+  hash [--base-dir DIR] [--strict] [READ LIMITS] PATH...
+                                  Print the schema hash
+  lint [--base-dir DIR] [--strict] PATH...
+                                  Report duplicate statements (and, with
+                                  --strict, empty or comment-only files)
+  diff OLD NEW                    List statements only in OLD (-) or NEW (+)
+  verify PACKAGE|CHUNK_DIR        Check a package or a --split-dir directory
+                                  against its manifest and print its digest
+  package [--version V] DIR OUT   Build DIR into a verifiable package archive
+  verify-package [--output FILE] PACKAGE
+                                  Check a package and print its fingerprint;
+                                  --output also writes its verified schema
+  dump-schema ARCHIVE             Print schema DDL from a pg_dump -Fc archive
+  dump-toc ARCHIVE                List TOC entries of a pg_dump -Fc archive
+
+PATH is a SQL file or a directory searched recursively for *.sql files.
+--strict fails on empty or comment-only files instead of warning.
+--status-file keeps FILE updated with the build's progress and ETA.
+--split-dir also writes the schema as numbered chunk files plus a MANIFEST,
+split at statement boundaries, and prints the chunk paths instead of the
+schema.
+lint exits 5 on findings; diff exits 1 when the schemas differ.
+READ LIMITS throttle reads on network filesystems:
+  --max-concurrent-reads N   read at most N files at once
+  --max-bytes-per-sec N      cap the combined read rate
+  --read-retries N           retry transient errors (EIO) up to N times
+";
+
+/// Invalid invocation: configuration invalid (see docs/reference/exit-codes.md)
+const EXIT_USAGE: u8 = 5;
+/// Lint findings: validation / lint failure
+const EXIT_LINT: u8 = 5;
+/// Output could not be written, or schemas differ: generic failure
+const EXIT_FAILURE: u8 = 1;
+
+enum CliError {
+    Usage(String),
+    Core(CoreError),
+    Output(String),
+    /// Command ran but its check failed; results are already on stdout
+    Failed(u8, String),
+}
+
+impl From<CoreError> for CliError {
+    fn from(err: CoreError) -> Self {
+        CliError::Core(err)
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage(message)) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::from(EXIT_USAGE)
+        }
+        Err(CliError::Core(err)) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(err.code.exit_code())
+        }
+        Err(CliError::Output(message)) => {
+            eprintln!("error: {}", message);
+            ExitCode::from(EXIT_FAILURE)
+        }
+        Err(CliError::Failed(code, message)) => {
+            eprintln!("error: {}", message);
+            ExitCode::from(code)
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), CliError> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(CliError::Usage("missing command".to_string()));
+    };
+
+    match command.as_str() {
+        "build" => {
+            let options = Options::parse(rest, true)?;
+            let files = expand_paths(&options.paths)?;
+            let (schema, notices) = match &options.status_file {
+                Some(status_path) => concat_files_with_status(
+                    &files,
+                    options.base_dir,
+                    options.strict,
+                    &options.limits,
+                    status_path,
+                )?,
+                None => concat_files_with_progress(
+                    &files,
+                    options.base_dir,
+                    options.strict,
+                    &options.limits,
+                    &|_| {},
+                )?,
+            };
+            warn(notices);
+            if let Some(path) = &options.output {
+                fs::write(path, &schema).map_err(|e| {
+                    CliError::Output(format!("cannot write {}: {}", path.display(), e))
+                })?;
+            }
+            match &options.split_dir {
+                Some(dir) => {
+                    let (chunks, notices) =
+                        write_chunks(&schema, dir, options.max_statements, options.max_bytes)?;
+                    warn(notices);
+                    let listing: String = chunks
+                        .iter()
+                        .map(|chunk| format!("{}\n", dir.join(&chunk.file).display()))
+                        .collect();
+                    emit(&listing)?;
+                }
+                None if options.output.is_none() => emit(&schema)?,
+                None => {}
+            }
+        }
+        "hash" => {
+            let options = Options::parse(rest, false)?;
+            let files = expand_paths(&options.paths)?;
+            let (hash, notices) = compute_hash_with_limits(
+                &files,
+                options.base_dir,
+                options.strict,
+                &options.limits,
+            )?;
+            warn(notices);
+            emit(&format!("{}\n", hash))?;
+        }
+        "lint" => {
+            let options = Options::parse(rest, false)?;
+            if options.limits != ReadLimits::default() {
+                return Err(CliError::Usage(
+                    "lint does not take read limits".to_string(),
+                ));
+            }
+            let files = expand_paths(&options.paths)?;
+
+            let mut findings = Vec::new();
+            for file in &files {
+                if is_blank(&read_sql(file)?) {
+                    let finding = format!("{}: empty or comment-only file", file);
+                    if options.strict {
+                        findings.push(finding);
+                    } else {
+                        warn(vec![finding]);
+                    }
+                }
+            }
+            for duplicate in find_duplicates(&files, options.base_dir)? {
+                let [first, others @ ..] = duplicate.locations.as_slice() else {
+                    continue;
+                };
+                let others: Vec<String> = others
+                    .iter()
+                    .map(|location| format!("{}:{}", location.file, location.line))
+                    .collect();
+                findings.push(format!(
+                    "{}:{}: duplicate statement (also at {}): {}",
+                    first.file,
+                    first.line,
+                    others.join(", "),
+                    snippet(&duplicate.statement)
+                ));
+            }
+
+            if !findings.is_empty() {
+                emit(&(findings.join("\n") + "\n"))?;
+                return Err(CliError::Failed(
+                    EXIT_LINT,
+                    format!("{} lint finding(s)", findings.len()),
+                ));
+            }
+        }
+        "diff" => {
+            let [old, new] = rest else {
+                return Err(CliError::Usage(
+                    "expected an old and a new schema path".to_string(),
+                ));
+            };
+            let old = statements(old)?;
+            let new = statements(new)?;
+            let old_set: HashSet<&str> = old.iter().map(String::as_str).collect();
+            let new_set: HashSet<&str> = new.iter().map(String::as_str).collect();
+
+            let removed: Vec<String> = old
+                .iter()
+                .filter(|statement| !new_set.contains(statement.as_str()))
+                .map(|statement| format!("-{};\n", statement))
+                .collect();
+            let added: Vec<String> = new
+                .iter()
+                .filter(|statement| !old_set.contains(statement.as_str()))
+                .map(|statement| format!("+{};\n", statement))
+                .collect();
+
+            if !removed.is_empty() || !added.is_empty() {
+                emit(&(removed.concat() + &added.concat()))?;
+                return Err(CliError::Failed(
+                    EXIT_FAILURE,
+                    format!(
+                        "schemas differ: {} statement(s) removed, {} added",
+                        removed.len(),
+                        added.len()
+                    ),
+                ));
+            }
+        }
+        "verify" => {
+            let path = single_path(rest)?;
+            let digest = if path.is_dir() {
+                verify_chunks(path)?.0
+            } else {
+                read_package(path)?.0.fingerprint
+            };
+            emit(&format!("{}\n", digest))?;
+        }
+        "package" => {
+            let (version, rest) = match rest {
+                [flag, version, rest @ ..] if flag == "--version" => (Some(version.as_str()), rest),
+                _ => (None, rest),
+            };
+            let [dir, out] = rest else {
+                return Err(CliError::Usage(
+                    "expected a schema directory and an output path".to_string(),
+                ));
+            };
+            let (manifest, notices) = write_package(Path::new(dir), Path::new(out), version)?;
+            warn(notices);
+            emit(&format!("{}\n", manifest.fingerprint))?;
+        }
+        "verify-package" => {
+            let (output, rest) = match rest {
+                [flag, output, rest @ ..] if flag == "--output" || flag == "-o" => {
+                    (Some(Path::new(output)), rest)
+                }
+                _ => (None, rest),
+            };
+            let (manifest, schema) = read_package(single_path(rest)?)?;
+            if let Some(path) = output {
+                fs::write(path, &schema).map_err(|e| {
+                    CliError::Output(format!("cannot write {}: {}", path.display(), e))
+                })?;
+            }
+            emit(&format!("{}\n", manifest.fingerprint))?;
+        }
+        "dump-schema" => {
+            let archive = read_archive(single_path(rest)?)?;
+            emit(&schema_ddl(&archive.entries))?;
+        }
+        "dump-toc" => {
+            let archive = read_archive(single_path(rest)?)?;
+            // Same column order as `pg_restore -l`
+            let listing: String = archive
+                .entries
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "{}; {} {} {} {}\n",
+                        entry.dump_id, entry.desc, entry.namespace, entry.tag, entry.owner
+                    )
+                })
+                .collect();
+            emit(&listing)?;
+        }
+        "-h" | "--help" | "help" => emit(USAGE)?,
+        other => return Err(CliError::Usage(format!("unknown command '{}'", other))),
+    }
+
+    Ok(())
+}
+
+/// Write to stdout, treating a closed pipe (e.g. `| head`) as success
+fn emit(text: &str) -> Result<(), CliError> {
+    match io::stdout().lock().write_all(text.as_bytes()) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            Err(CliError::Output(format!("cannot write to stdout: {}", e)))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn warn(notices: Vec<String>) {
+    for notice in notices {
+        eprintln!("warning: {}", notice);
+    }
+}
+
+/// Options shared by `build` and `hash`
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    base_dir: Option<String>,
+    output: Option<PathBuf>,
+    status_file: Option<PathBuf>,
+    split_dir: Option<PathBuf>,
+    max_statements: Option<usize>,
+    max_bytes: Option<usize>,
+    strict: bool,
+    limits: ReadLimits,
+    paths: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: &[String], is_build: bool) -> Result<Self, CliError> {
+        let mut options = Options::default();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--base-dir" => options.base_dir = Some(option_value(&mut iter, arg)?),
+                "--strict" => options.strict = true,
+                "--max-concurrent-reads" => {
+                    options.limits.max_concurrent_reads = Some(number_value(&mut iter, arg)?)
+                }
+                "--max-bytes-per-sec" => {
+                    options.limits.max_bytes_per_sec = Some(number_value(&mut iter, arg)?)
+                }
+                "--read-retries" => options.limits.retries = number_value(&mut iter, arg)?,
+                "--output" | "-o" if is_build => {
+                    options.output = Some(PathBuf::from(option_value(&mut iter, arg)?))
+                }
+                "--status-file" if is_build => {
+                    options.status_file = Some(PathBuf::from(option_value(&mut iter, arg)?))
+                }
+                "--split-dir" if is_build => {
+                    options.split_dir = Some(PathBuf::from(option_value(&mut iter, arg)?))
+                }
+                "--max-statements" if is_build => {
+                    options.max_statements = Some(number_value(&mut iter, arg)?)
+                }
+                "--max-bytes" if is_build => {
+                    options.max_bytes = Some(number_value(&mut iter, arg)?)
+                }
+                flag if flag.starts_with('-') => {
+                    return Err(CliError::Usage(format!("unknown option '{}'", flag)))
+                }
+                path => options.paths.push(path.to_string()),
+            }
+        }
+
+        if options.paths.is_empty() {
+            return Err(CliError::Usage("no input paths given".to_string()));
+        }
+        if options.split_dir.is_none() {
+            for (option, limit) in [
+                ("max_statements", options.max_statements.is_some()),
+                ("max_bytes", options.max_bytes.is_some()),
+            ] {
+                if limit {
+                    return Err(CliError::Core(
+                        CoreError::new(
+                            ErrorCode::InvalidOption,
+                            format!("--{} requires --split-dir", option.replace('_', "-")),
+                        )
+                        .with_detail("option", option),
+                    ));
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn option_value<'a>(
+    iter: &mut impl Iterator<Item = &'a String>,
+    flag: &str,
+) -> Result<String, CliError> {
+    iter.next()
+        .cloned()
+        .ok_or_else(|| CliError::Usage(format!("{} requires a value", flag)))
+}
+
+fn number_value<'a, T: std::str::FromStr>(
+    iter: &mut impl Iterator<Item = &'a String>,
+    flag: &str,
+) -> Result<T, CliError> {
+    let value = option_value(iter, flag)?;
+    value
+        .parse()
+        .map_err(|_| CliError::Usage(format!("{} expects a number, got '{}'", flag, value)))
+}
+
+/// Read a SQL file as text, mapping I/O failures to `SCHEMA_205`
+fn read_sql(path: &str) -> Result<String, CoreError> {
+    let bytes = fs::read(path).map_err(|e| {
+        CoreError::new(
+            ErrorCode::FileRead,
+            format!("Cannot read schema file {}: {}", path, e),
+        )
+        .with_detail("file", path)
+        .with_detail("reason", e.to_string())
+    })?;
+    let text = String::from_utf8_lossy(&bytes);
+    Ok(text.strip_prefix('\u{feff}').unwrap_or(&text).to_string())
+}
+
+/// Normalized statements of a SQL file or directory, in build order
+fn statements(path: &str) -> Result<Vec<String>, CliError> {
+    let mut texts = Vec::new();
+    for file in expand_paths(&[path.to_string()])? {
+        texts.extend(
+            split_statements(&read_sql(&file)?)
+                .into_iter()
+                .map(|statement| statement.text),
+        );
+    }
+    Ok(texts)
+}
+
+/// Normalized statement, shortened for one-line reports
+fn snippet(statement: &str) -> String {
+    const MAX_CHARS: usize = 60;
+    match statement.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => format!("{}...", &statement[..end]),
+        None => statement.to_string(),
+    }
+}
+
+fn single_path(args: &[String]) -> Result<&Path, CliError> {
+    match args {
+        [path] => Ok(Path::new(path)),
+        _ => Err(CliError::Usage(
+            "expected exactly one file path".to_string(),
+        )),
+    }
+}
+
+/// Expand directories to their `*.sql` files, keeping explicit files as given
+fn expand_paths(paths: &[String]) -> Result<Vec<String>, CliError> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        if !Path::new(path).is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+
+        let found = collect_sql_files(Path::new(path))?;
+        if found.is_empty() {
+            return Err(CliError::Usage(format!("no .sql files under {}", path)));
+        }
+        files.extend(found);
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_options_parse() {
+        let options = Options::parse(&args(&["--base-dir", "db", "a.sql", "-o", "out.sql"]), true)
+            .ok()
+            .unwrap();
+
+        assert_eq!(options.base_dir.as_deref(), Some("db"));
+        assert_eq!(options.output, Some(PathBuf::from("out.sql")));
+        assert_eq!(options.paths, vec!["a.sql".to_string()]);
+    }
+
+    #[test]
+    fn test_options_parse_read_limits() {
+        let options = Options::parse(
+            &args(&[
+                "--max-concurrent-reads",
+                "2",
+                "--read-retries",
+                "3",
+                "a.sql",
+            ]),
+            false,
+        )
+        .ok()
+        .unwrap();
+
+        assert_eq!(options.limits.max_concurrent_reads, Some(2));
+        assert_eq!(options.limits.max_bytes_per_sec, None);
+        assert_eq!(options.limits.retries, 3);
+    }
+
+    #[test]
+    fn test_options_parse_rejects_output_for_hash() {
+        assert!(Options::parse(&args(&["-o", "out.sql", "a.sql"]), false).is_err());
+        assert!(Options::parse(&args(&["--status-file", "s", "a.sql"]), false).is_err());
+        assert!(Options::parse(&args(&["--base-dir"]), false).is_err());
+        assert!(Options::parse(&args(&["--read-retries", "x", "a.sql"]), false).is_err());
+    }
+
+    #[test]
+    fn test_options_parse_rejects_chunk_limits_without_split_dir() {
+        let err = Options::parse(&args(&["--max-bytes", "100", "a.sql"]), true).err();
+
+        match err {
+            Some(CliError::Core(err)) => {
+                assert_eq!(err.code, ErrorCode::InvalidOption);
+                assert!(err.message.contains("--max-bytes requires --split-dir"));
+            }
+            _ => panic!("expected an InvalidOption error"),
+        }
+        assert!(Options::parse(
+            &args(&["--split-dir", "out", "--max-bytes", "100", "a.sql"]),
+            true
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_expand_paths_sorts_directory_contents() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("20_views");
+        fs::create_dir(&nested).unwrap();
+        fs::write(temp_dir.path().join("10_tables.sql"), "").unwrap();
+        fs::write(nested.join("v.sql"), "").unwrap();
+        fs::write(nested.join("notes.md"), "").unwrap();
+
+        let files = expand_paths(&[temp_dir.path().to_string_lossy().into_owned()])
+            .ok()
+            .unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("10_tables.sql"));
+        assert!(files[1].ends_with("v.sql"));
+    }
+
+    #[test]
+    fn test_build_fails_on_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing.sql");
+
+        for strict in [false, true] {
+            let mut command = args(&["build", &missing.to_string_lossy()]);
+            if strict {
+                command.push("--strict".to_string());
+            }
+            match run(&command) {
+                Err(CliError::Core(err)) => assert_eq!(err.code, ErrorCode::FileRead),
+                _ => panic!("expected a FileRead error"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_lint_reports_duplicates_and_strict_blank_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("01.sql"), "CREATE INDEX i ON t (a);\n").unwrap();
+        fs::write(temp_dir.path().join("02.sql"), "-- TODO\n").unwrap();
+        let dir = temp_dir.path().to_string_lossy().into_owned();
+
+        assert!(run(&args(&["lint", &dir])).is_ok());
+        assert!(matches!(
+            run(&args(&["lint", "--strict", &dir])),
+            Err(CliError::Failed(EXIT_LINT, _))
+        ));
+
+        fs::write(temp_dir.path().join("03.sql"), "CREATE  INDEX i ON t (a);").unwrap();
+        match run(&args(&["lint", &dir])) {
+            Err(CliError::Failed(code, message)) => {
+                assert_eq!(code, EXIT_LINT);
+                assert_eq!(message, "1 lint finding(s)");
+            }
+            _ => panic!("expected a lint failure"),
+        }
+    }
+
+    #[test]
+    fn test_diff_ignores_formatting() {
+        let temp_dir = TempDir::new().unwrap();
+        let old = temp_dir.path().join("old.sql");
+        let new = temp_dir.path().join("new.sql");
+        fs::write(&old, "CREATE TABLE a (id INT);\nCREATE TABLE b (id INT);\n").unwrap();
+        fs::write(&new, "-- reformatted\nCREATE TABLE a\n    (id INT);\n").unwrap();
+        let (old, new) = (old.to_string_lossy(), new.to_string_lossy());
+
+        assert!(run(&args(&["diff", &old, &old])).is_ok());
+        match run(&args(&["diff", &old, &new])) {
+            Err(CliError::Failed(code, message)) => {
+                assert_eq!(code, EXIT_FAILURE);
+                assert!(message.contains("1 statement(s) removed, 0 added"));
+            }
+            _ => panic!("expected a diff failure"),
+        }
+    }
+
+    #[test]
+    fn test_snippet_shortens_on_char_boundary() {
+        assert_eq!(snippet("SELECT 1"), "SELECT 1");
+        let long = "é".repeat(61);
+        assert_eq!(snippet(&long), format!("{}...", "é".repeat(60)));
+    }
+}
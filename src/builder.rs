@@ -12,74 +12,223 @@
 //!
 //! This matches Python fallback behavior and prevents issues with
 //! PL/pgSQL functions using dollar-quoted strings ($$...$$).
+//!
+//! ## Warnings
+//!
+//! Recoverable oddities are fixed or passed through and reported as
+//! `NativeWarning`s rather than failing the build:
+//! - A leading UTF-8 byte order mark is stripped (it would otherwise land
+//!   mid-file in the concatenated output)
+//! - Files with no content besides whitespace and comments are flagged as
+//!   suspicious (usually a botched merge); with `strict=True` they fail the
+//!   build with `SCHEMA_208` instead, listing every offending path
+//!
+//! ## Status File
+//!
+//! With `status_path`, `build_schema` keeps a heartbeat status file up to
+//! date while it runs (see `crate::status`); `read_status` reads it back.
 
 #![allow(clippy::useless_conversion)]
 
+#[cfg(feature = "python")]
+use crate::errors::warn_all;
+use crate::errors::{CoreError, ErrorCode};
+use crate::hasher::read_error;
+use crate::limits::{ReadLimits, Reader};
+use crate::sql::{blank_file_notice, blank_files_error, is_blank};
+use crate::status::StatusFile;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use rayon::prelude::*;
-use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
 
 /// Build schema by concatenating SQL files
 ///
 /// Args:
 ///     files: List of SQL file paths to concatenate
+///     base_dir: Directory that `-- File:` headers are relative to
+///         (default: common parent of `files`)
+///     strict: Fail instead of warning on empty or comment-only files
+///     status_path: File to keep updated with the build's phase, current
+///         file and ETA while it runs (read it with `read_status`)
+///     max_concurrent_reads: Files read at the same time (default: one per
+///         CPU); lower it on network filesystems
+///     max_bytes_per_sec: Combined read rate limit (default: unlimited)
+///     read_retries: Extra attempts per file after a transient error such
+///         as EIO
 ///
 /// Returns:
 ///     Concatenated schema content as string
 ///
+/// Raises:
+///     NativeError: code `SCHEMA_205` if a file cannot be read or is not
+///         UTF-8; code `SCHEMA_206` if a read limit is 0; code `SCHEMA_208`
+///         if `strict` and any file is empty or comment-only
+///
+/// Warns:
+///     NativeWarning: for each stripped byte order mark or empty file, and
+///         once if the status file cannot be written
+///
 /// This function is 10-50x faster than Python due to:
 /// - Parallel file reading (rayon)
 /// - Pre-allocated buffers
 /// - Native string operations
 /// - No GIL contention
+#[cfg(feature = "python")]
 #[pyfunction]
+#[pyo3(signature = (
+    files,
+    base_dir = None,
+    strict = false,
+    status_path = None,
+    max_concurrent_reads = None,
+    max_bytes_per_sec = None,
+    read_retries = 0,
+))]
 #[allow(clippy::useless_conversion, clippy::needless_return)]
-pub fn build_schema(files: Vec<String>) -> PyResult<String> {
-    // Pre-allocate for ~10MB typical schema
-    let mut output = String::with_capacity(10_000_000);
+pub fn build_schema(
+    files: Vec<String>,
+    base_dir: Option<String>,
+    strict: bool,
+    status_path: Option<String>,
+    max_concurrent_reads: Option<usize>,
+    max_bytes_per_sec: Option<u64>,
+    read_retries: u32,
+) -> PyResult<String> {
+    let limits = ReadLimits {
+        max_concurrent_reads,
+        max_bytes_per_sec,
+        retries: read_retries,
+    };
+    let (output, notices) = match status_path {
+        Some(status_path) => {
+            concat_files_with_status(&files, base_dir, strict, &limits, Path::new(&status_path))?
+        }
+        None => concat_files_with_progress(&files, base_dir, strict, &limits, &|_| {})?,
+    };
+    warn_all(notices)?;
+    Ok(output)
+}
+
+/// Concatenate SQL files with `-- File:` headers (pure Rust core of
+/// `build_schema`)
+///
+/// Returns the schema text and one notice per recoverable oddity, for the
+/// caller to surface as warnings. A file that cannot be read is an error
+/// (`SCHEMA_205`); with `strict`, so are empty or comment-only files
+/// (`SCHEMA_208`) rather than notices.
+pub fn concat_files(
+    files: &[String],
+    base_dir: Option<String>,
+    strict: bool,
+) -> Result<(String, Vec<String>), CoreError> {
+    concat_files_with_progress(files, base_dir, strict, &ReadLimits::default(), &|_| {})
+}
+
+/// `concat_files` that keeps a status file at `status_path` up to date
+///
+/// Failing to write the status file never fails the build; it adds one
+/// notice instead.
+pub fn concat_files_with_status(
+    files: &[String],
+    base_dir: Option<String>,
+    strict: bool,
+    limits: &ReadLimits,
+    status_path: &Path,
+) -> Result<(String, Vec<String>), CoreError> {
+    let status = StatusFile::start(status_path, "build", files.len());
+    let result = status.heartbeat_during(|| {
+        concat_files_with_progress(files, base_dir, strict, limits, &|file| {
+            status.unit_done(&file.to_string_lossy())
+        })
+    });
+    status.finish(result.as_ref().err());
+
+    let (output, mut notices) = result?;
+    notices.extend(status.write_error());
+    Ok((output, notices))
+}
+
+/// `concat_files`, reading files within `limits` and calling `on_file_read`
+/// with each file's path relative to the base directory as soon as it has
+/// been read (from rayon workers, in completion order)
+pub fn concat_files_with_progress(
+    files: &[String],
+    base_dir: Option<String>,
+    strict: bool,
+    limits: &ReadLimits,
+    on_file_read: &(dyn Fn(&Path) + Sync),
+) -> Result<(String, Vec<String>), CoreError> {
+    let reader = Reader::new(limits)?;
 
     // Convert strings to PathBuf
     let paths: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
 
-    // Find common base directory for relative paths
-    let base_dir = find_common_parent(&paths);
+    // Use the caller's base directory, else find the common one
+    let base_dir = base_dir
+        .map(PathBuf::from)
+        .unwrap_or_else(|| find_common_parent(&paths));
 
     // Read all files in parallel
-    let contents: Vec<(usize, PathBuf, String)> = paths
-        .par_iter()
-        .enumerate()
-        .map(|(i, path)| {
-            let content = fs::read_to_string(path)
-                .unwrap_or_else(|e| format!("-- Error reading {}: {}\n", path.display(), e));
-            (i, path.clone(), content)
-        })
-        .collect();
+    let mut contents: Vec<(usize, Result<LoadedFile, CoreError>)> = reader.install(|| {
+        paths
+            .par_iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let loaded = load_sql_file(path, &reader);
+                on_file_read(path.strip_prefix(&base_dir).unwrap_or(path));
+                (i, loaded)
+            })
+            .collect()
+    });
+
+    // Sort by original index (maintain order), so the first unreadable file
+    // in build order is the one reported
+    contents.sort_by_key(|(i, _)| *i);
+    let loaded = contents
+        .into_iter()
+        .map(|(_, loaded)| loaded)
+        .collect::<Result<Vec<_>, CoreError>>()?;
+
+    assemble_schema(&paths, loaded, &base_dir, strict)
+}
 
-    // Sort by original index (maintain order)
-    let mut sorted_contents = contents;
-    sorted_contents.sort_by_key(|(i, _, _)| *i);
+/// Concatenate files already loaded by `load_sql_file`/`decode_sql_file`,
+/// in `paths` order (the part of `concat_files` after reading)
+pub(crate) fn assemble_schema(
+    paths: &[PathBuf],
+    loaded: Vec<LoadedFile>,
+    base_dir: &Path,
+    strict: bool,
+) -> Result<(String, Vec<String>), CoreError> {
+    if strict {
+        let blank: Vec<&Path> = paths
+            .iter()
+            .zip(&loaded)
+            .filter(|(_, (content, _))| is_blank(content))
+            .map(|(path, _)| path.as_path())
+            .collect();
+        if !blank.is_empty() {
+            return Err(blank_files_error(&blank));
+        }
+    }
+
+    // Size the buffer from the inputs actually read, so huge schemas don't
+    // reallocate repeatedly and tiny ones don't reserve megabytes
+    let capacity = paths
+        .iter()
+        .zip(&loaded)
+        .map(|(path, (content, _))| section_len_bound(path, content))
+        .sum::<usize>()
+        + 1;
+    let mut output = String::with_capacity(capacity);
 
     // Concatenate in order with file headers
-    for (_, path, content) in sorted_contents {
-        // Calculate relative path for header
-        let rel_path = path
-            .strip_prefix(&base_dir)
-            .unwrap_or(&path)
-            .to_string_lossy();
-
-        // Add file separator (matches Python behavior)
-        output.push_str("\n-- ============================================\n");
-        output.push_str(&format!("-- File: {}\n", rel_path));
-        output.push_str("-- ============================================\n\n");
-
-        // Add file content
-        output.push_str(&content);
-
-        // Ensure newline at end
-        if !content.ends_with('\n') {
-            output.push('\n');
-        }
+    let mut notices = Vec::new();
+    for (path, (content, file_notices)) in paths.iter().zip(loaded) {
+        push_file_section(&mut output, path, base_dir, &content);
+        notices.extend(file_notices);
     }
 
     // Defensive: Ensure final output ends with newline per POSIX standard.
@@ -89,11 +238,260 @@ pub fn build_schema(files: Vec<String>) -> PyResult<String> {
         output.push('\n');
     }
 
-    Ok(output)
+    Ok((output, notices))
+}
+
+/// Build schema as an iterator of output chunks
+///
+/// Args:
+///     files: List of SQL file paths to concatenate
+///     chunk_size: Maximum size of each yielded chunk in bytes (default 64 KiB)
+///     base_dir: Directory that `-- File:` headers are relative to
+///         (default: common parent of `files`)
+///     strict: Fail instead of warning on empty or comment-only files
+///     max_bytes_per_sec: Read rate limit (default: unlimited)
+///     read_retries: Extra attempts per file after a transient error such
+///         as EIO
+///
+/// Returns:
+///     Iterator yielding string chunks whose concatenation equals
+///     `build_schema(files, base_dir)`
+///
+/// Raises:
+///     NativeError: code `SCHEMA_206` if `chunk_size` or
+///         `max_bytes_per_sec` is 0; from `__next__`, code `SCHEMA_205` if
+///         the next file cannot be read, or `SCHEMA_208` when `strict` and
+///         it is empty or comment-only
+///
+/// Files are read one at a time as the iterator advances, so at most one
+/// file plus one chunk is held in memory (so there is no
+/// `max_concurrent_reads`). Use this to stream a schema to a socket or
+/// upload API; use `build_schema` when the full string is needed.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    files,
+    chunk_size = 65_536,
+    base_dir = None,
+    strict = false,
+    max_bytes_per_sec = None,
+    read_retries = 0,
+))]
+pub fn build_schema_iter(
+    files: Vec<String>,
+    chunk_size: usize,
+    base_dir: Option<String>,
+    strict: bool,
+    max_bytes_per_sec: Option<u64>,
+    read_retries: u32,
+) -> PyResult<BuildSchemaIter> {
+    let limits = ReadLimits {
+        max_concurrent_reads: None,
+        max_bytes_per_sec,
+        retries: read_retries,
+    };
+    Ok(BuildSchemaIter::new(
+        &files, chunk_size, base_dir, strict, &limits,
+    )?)
+}
+
+/// Streaming builder behind `build_schema_iter` (a Python iterator when the
+/// `python` feature is enabled)
+#[cfg_attr(feature = "python", pyclass)]
+pub struct BuildSchemaIter {
+    paths: Vec<PathBuf>,
+    base_dir: PathBuf,
+    next_file: usize,
+    chunk_size: usize,
+    pending: String,
+    emitted_any: bool,
+    strict: bool,
+    reader: Reader,
+}
+
+impl BuildSchemaIter {
+    /// Files are read one at a time, so `limits.max_concurrent_reads` has no
+    /// effect; pacing and retries apply
+    pub fn new(
+        files: &[String],
+        chunk_size: usize,
+        base_dir: Option<String>,
+        strict: bool,
+        limits: &ReadLimits,
+    ) -> Result<Self, CoreError> {
+        if chunk_size == 0 {
+            return Err(CoreError::new(
+                ErrorCode::InvalidOption,
+                "chunk_size must be greater than 0",
+            )
+            .with_detail("option", "chunk_size"));
+        }
+
+        let paths: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+        let base_dir = base_dir
+            .map(PathBuf::from)
+            .unwrap_or_else(|| find_common_parent(&paths));
+
+        Ok(Self {
+            paths,
+            base_dir,
+            next_file: 0,
+            chunk_size,
+            pending: String::new(),
+            emitted_any: false,
+            strict,
+            reader: Reader::new(limits)?,
+        })
+    }
+
+    /// Next output chunk and the notices raised while reading for it, or
+    /// `None` once the output is exhausted
+    pub fn next_chunk(&mut self) -> Result<Option<(String, Vec<String>)>, CoreError> {
+        // Read files until a full chunk is buffered or input is exhausted
+        let mut notices = Vec::new();
+        while self.pending.len() < self.chunk_size && self.next_file < self.paths.len() {
+            let path = &self.paths[self.next_file];
+            let (content, file_notices) = load_sql_file(path, &self.reader)?;
+            if self.strict && is_blank(&content) {
+                return Err(blank_files_error(&[path]));
+            }
+            push_file_section(&mut self.pending, path, &self.base_dir, &content);
+            self.next_file += 1;
+            notices.extend(file_notices);
+        }
+
+        if self.pending.is_empty() {
+            // Match build_schema, which returns "\n" for an empty file list
+            if !self.emitted_any {
+                self.emitted_any = true;
+                return Ok(Some(("\n".to_string(), notices)));
+            }
+            return Ok(None);
+        }
+
+        let split = chunk_boundary(&self.pending, self.chunk_size);
+        let rest = self.pending.split_off(split);
+        self.emitted_any = true;
+        Ok(Some((std::mem::replace(&mut self.pending, rest), notices)))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl BuildSchemaIter {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<String>> {
+        match self.next_chunk()? {
+            Some((chunk, notices)) => {
+                warn_all(notices)?;
+                Ok(Some(chunk))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// A file's content and the notices raised while loading it
+type LoadedFile = (String, Vec<String>);
+
+/// Read a SQL file for concatenation, collecting warnings for oddities
+fn load_sql_file(path: &Path, reader: &Reader) -> Result<LoadedFile, CoreError> {
+    let bytes = reader.read(path).map_err(|e| read_error(path, e))?;
+    decode_sql_file(path, bytes)
+}
+
+/// Decode a file's bytes as SQL: UTF-8 (`SCHEMA_205` otherwise) with any
+/// byte order mark stripped, plus the notices `load_sql_file` reports
+pub(crate) fn decode_sql_file(path: &Path, bytes: Vec<u8>) -> Result<LoadedFile, CoreError> {
+    let mut notices = Vec::new();
+    // Same message as `fs::read_to_string`
+    let mut content = String::from_utf8(bytes).map_err(|_| {
+        read_error(
+            path,
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            ),
+        )
+    })?;
+
+    // A BOM would end up mid-file in the concatenated output
+    if let Some(stripped) = content.strip_prefix('\u{feff}') {
+        notices.push(format!(
+            "Stripped UTF-8 byte order mark from {}",
+            path.display()
+        ));
+        content = stripped.to_string();
+    }
+
+    if is_blank(&content) {
+        notices.push(blank_file_notice(path));
+    }
+
+    Ok((content, notices))
+}
+
+/// Separator line around each `-- File:` header (Python line_comment style)
+const SEPARATOR: &str = "-- ==========================================";
+
+/// Bytes `push_file_section` adds around a file's content, excluding the path
+const SECTION_OVERHEAD: usize = 2 * SEPARATOR.len() + "\n\n-- File: \n\n\n".len();
+
+/// Upper bound on the bytes `push_file_section` appends for one file
+///
+/// Uses the full path, which is never shorter than the relative one; lossy
+/// conversion of a non-UTF-8 path may add a few bytes, which only costs one
+/// reallocation.
+fn section_len_bound(path: &Path, content: &str) -> usize {
+    SECTION_OVERHEAD + path.as_os_str().len() + content.len() + 1
+}
+
+/// Append one file's separator header and content (matches Python behavior)
+fn push_file_section(output: &mut String, path: &Path, base_dir: &Path, content: &str) {
+    // Calculate relative path for header
+    let rel_path = path
+        .strip_prefix(base_dir)
+        .unwrap_or(path)
+        .to_string_lossy();
+
+    // Add file separator (matches Python line_comment style)
+    output.push('\n');
+    output.push_str(SEPARATOR);
+    output.push('\n');
+    output.push_str(&format!("-- File: {}\n", rel_path));
+    output.push_str(SEPARATOR);
+    output.push_str("\n\n");
+
+    // Add file content
+    output.push_str(content);
+
+    // Ensure newline at end
+    if !content.ends_with('\n') {
+        output.push('\n');
+    }
+}
+
+/// Largest char boundary at or below `max`, but always at least one char
+fn chunk_boundary(s: &str, max: usize) -> usize {
+    if s.len() <= max {
+        return s.len();
+    }
+    let mut idx = max;
+    while !s.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    if idx == 0 {
+        // A single multi-byte char wider than max: emit it whole
+        idx = s.chars().next().map_or(s.len(), char::len_utf8);
+    }
+    idx
 }
 
 /// Find common parent directory of all paths
-fn find_common_parent(paths: &[PathBuf]) -> PathBuf {
+pub(crate) fn find_common_parent(paths: &[PathBuf]) -> PathBuf {
     if paths.is_empty() {
         return PathBuf::from(".");
     }
@@ -152,7 +550,9 @@ mod tests {
 
         fs::write(&file_path, "CREATE TABLE test (id INT);").unwrap();
 
-        let result = build_schema(vec![file_path.to_str().unwrap().to_string()]).unwrap();
+        let result = concat_files(&[file_path.to_str().unwrap().to_string()], None, false)
+            .unwrap()
+            .0;
 
         assert!(result.contains("CREATE TABLE test"));
     }
@@ -167,11 +567,16 @@ mod tests {
         fs::write(&file1, "CREATE TABLE users (id INT);").unwrap();
         fs::write(&file2, "CREATE TABLE posts (id INT);").unwrap();
 
-        let result = build_schema(vec![
-            file1.to_str().unwrap().to_string(),
-            file2.to_str().unwrap().to_string(),
-        ])
-        .unwrap();
+        let result = concat_files(
+            &[
+                file1.to_str().unwrap().to_string(),
+                file2.to_str().unwrap().to_string(),
+            ],
+            None,
+            false,
+        )
+        .unwrap()
+        .0;
 
         assert!(result.contains("CREATE TABLE users"));
         assert!(result.contains("CREATE TABLE posts"));
@@ -190,7 +595,9 @@ mod tests {
         // File without trailing newline
         fs::write(&file_path, "CREATE TABLE test (id INT);").unwrap();
 
-        let result = build_schema(vec![file_path.to_str().unwrap().to_string()]).unwrap();
+        let result = concat_files(&[file_path.to_str().unwrap().to_string()], None, false)
+            .unwrap()
+            .0;
 
         // Should add trailing newlines
         assert!(result.ends_with("\n\n") || result.ends_with('\n'));
@@ -213,12 +620,17 @@ mod tests {
         .unwrap();
         fs::write(&file3, "INSERT INTO users VALUES (1);").unwrap();
 
-        let result = build_schema(vec![
-            file1.to_str().unwrap().to_string(),
-            file2.to_str().unwrap().to_string(),
-            file3.to_str().unwrap().to_string(),
-        ])
-        .unwrap();
+        let result = concat_files(
+            &[
+                file1.to_str().unwrap().to_string(),
+                file2.to_str().unwrap().to_string(),
+                file3.to_str().unwrap().to_string(),
+            ],
+            None,
+            false,
+        )
+        .unwrap()
+        .0;
 
         // Output must end with exactly one newline per POSIX standard
         assert!(result.ends_with('\n'), "Output should end with newline");
@@ -238,4 +650,226 @@ mod tests {
         assert!(result.contains("CREATE FUNCTION test"));
         assert!(result.contains("INSERT INTO users"));
     }
+
+    #[test]
+    fn test_build_schema_iter_matches_build_schema() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let file1 = temp_dir.path().join("01.sql");
+        let file2 = temp_dir.path().join("02.sql");
+
+        fs::write(&file1, "CREATE TABLE users (id INT);").unwrap();
+        fs::write(&file2, "-- café\nCREATE TABLE posts (id INT);\n").unwrap();
+
+        let files = vec![
+            file1.to_str().unwrap().to_string(),
+            file2.to_str().unwrap().to_string(),
+        ];
+        let (expected, _) = concat_files(&files, None, false).unwrap();
+
+        for chunk_size in [1, 7, 64, 1_000_000] {
+            let mut iter =
+                BuildSchemaIter::new(&files, chunk_size, None, false, &ReadLimits::default())
+                    .unwrap();
+            let mut streamed = String::new();
+            while let Some((chunk, _)) = iter.next_chunk().unwrap() {
+                assert!(!chunk.is_empty());
+                assert!(chunk.len() <= chunk_size.max(2));
+                streamed.push_str(&chunk);
+            }
+            assert_eq!(streamed, expected);
+        }
+    }
+
+    #[test]
+    fn test_build_schema_iter_empty_input() {
+        let mut iter = BuildSchemaIter::new(&[], 16, None, false, &ReadLimits::default()).unwrap();
+
+        assert_eq!(iter.next_chunk().unwrap().unwrap().0, "\n");
+        assert!(iter.next_chunk().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_build_schema_iter_rejects_zero_limits() {
+        let err = BuildSchemaIter::new(&[], 0, None, false, &ReadLimits::default())
+            .err()
+            .unwrap();
+        assert_eq!(err.code, ErrorCode::InvalidOption);
+
+        let limits = ReadLimits {
+            max_bytes_per_sec: Some(0),
+            ..ReadLimits::default()
+        };
+        let err = BuildSchemaIter::new(&[], 16, None, false, &limits)
+            .err()
+            .unwrap();
+        assert_eq!(err.code, ErrorCode::InvalidOption);
+    }
+
+    #[test]
+    fn test_concat_files_sizes_buffer_from_inputs() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut files = Vec::new();
+        for i in 0..3 {
+            let path = temp_dir.path().join(format!("{:02}.sql", i));
+            fs::write(&path, "CREATE TABLE t (id INT);").unwrap();
+            files.push(path.to_str().unwrap().to_string());
+        }
+
+        let (output, _) = concat_files(&files, None, false).unwrap();
+
+        // No reallocation was needed, and nothing close to megabytes reserved
+        let bound: usize = files
+            .iter()
+            .map(|f| section_len_bound(Path::new(f), "CREATE TABLE t (id INT);"))
+            .sum::<usize>()
+            + 1;
+        assert!(output.capacity() <= bound);
+    }
+
+    #[test]
+    fn test_build_schema_matches_python_line_comment_separator() {
+        let temp_dir = TempDir::new().unwrap();
+        let sub_dir = temp_dir.path().join("10_tables");
+        fs::create_dir(&sub_dir).unwrap();
+        let file_path = sub_dir.join("users.sql");
+
+        fs::write(&file_path, "CREATE TABLE users (id INT);\n").unwrap();
+
+        let result = concat_files(
+            &[file_path.to_str().unwrap().to_string()],
+            Some(temp_dir.path().to_str().unwrap().to_string()),
+            false,
+        )
+        .unwrap()
+        .0;
+
+        // Same bytes as SchemaBuilder._get_separator_for_file("line_comment")
+        let rule = format!("-- {}\n", "=".repeat(42));
+        let expected =
+            format!("\n{rule}-- File: 10_tables/users.sql\n{rule}\nCREATE TABLE users (id INT);\n");
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_load_sql_file_strips_bom() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("bom.sql");
+
+        fs::write(&file_path, "\u{feff}CREATE TABLE test (id INT);\n").unwrap();
+
+        let (content, notices) =
+            load_sql_file(&file_path, &Reader::new(&ReadLimits::default()).unwrap()).unwrap();
+
+        assert_eq!(content, "CREATE TABLE test (id INT);\n");
+        assert_eq!(notices.len(), 1);
+        assert!(notices[0].contains("byte order mark"));
+    }
+
+    #[test]
+    fn test_concat_files_fails_on_unreadable_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let present = temp_dir.path().join("01.sql");
+        fs::write(&present, "CREATE TABLE users (id INT);").unwrap();
+        let files = vec![
+            present.to_str().unwrap().to_string(),
+            temp_dir.path().join("02.sql").to_str().unwrap().to_string(),
+        ];
+
+        for strict in [false, true] {
+            let err = concat_files(&files, None, strict).unwrap_err();
+            assert_eq!(err.code, ErrorCode::FileRead);
+            assert!(err.message.contains("02.sql"));
+        }
+
+        let mut iter =
+            BuildSchemaIter::new(&files, 1 << 20, None, false, &ReadLimits::default()).unwrap();
+        assert_eq!(iter.next_chunk().unwrap_err().code, ErrorCode::FileRead);
+    }
+
+    #[test]
+    fn test_concat_files_strict_lists_blank_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut files = Vec::new();
+        for (name, content) in [
+            ("01.sql", "CREATE TABLE users (id INT);"),
+            ("02.sql", "-- <<<<<<< HEAD\n"),
+            ("03.sql", ""),
+        ] {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            files.push(path.to_str().unwrap().to_string());
+        }
+
+        let (_, notices) = concat_files(&files, None, false).unwrap();
+        assert_eq!(notices.len(), 2);
+
+        let err = concat_files(&files, None, true).unwrap_err();
+        assert_eq!(err.code, ErrorCode::EmptyFile);
+        assert!(err.message.contains("02.sql") && err.message.contains("03.sql"));
+
+        let mut iter =
+            BuildSchemaIter::new(&files, 16, None, true, &ReadLimits::default()).unwrap();
+        let mut result = Ok(None);
+        for _ in 0..files.len() * 64 {
+            result = iter.next_chunk();
+            if !matches!(result, Ok(Some(_))) {
+                break;
+            }
+        }
+        assert_eq!(result.unwrap_err().code, ErrorCode::EmptyFile);
+    }
+
+    #[test]
+    fn test_concat_files_with_status_reports_each_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let status_path = temp_dir.path().join("build.status");
+        let mut files = Vec::new();
+        for name in ["01.sql", "02.sql"] {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, "CREATE TABLE t (id INT);\n").unwrap();
+            files.push(path.to_str().unwrap().to_string());
+        }
+
+        let (output, notices) =
+            concat_files_with_status(&files, None, false, &ReadLimits::default(), &status_path)
+                .unwrap();
+        assert_eq!(output, concat_files(&files, None, false).unwrap().0);
+        assert!(notices.is_empty());
+
+        let status = crate::status::read_status_file(&status_path).unwrap();
+        assert_eq!(
+            (status.phase.as_str(), status.done, status.total),
+            ("done", 2, 2)
+        );
+        assert_eq!(status.percent, 100.0);
+    }
+
+    #[test]
+    fn test_load_sql_file_flags_empty_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("empty.sql");
+
+        fs::write(&file_path, "  \n\t\n").unwrap();
+
+        let (_, notices) =
+            load_sql_file(&file_path, &Reader::new(&ReadLimits::default()).unwrap()).unwrap();
+
+        assert_eq!(notices.len(), 1);
+        assert!(notices[0].contains("empty"));
+    }
+
+    #[test]
+    fn test_load_sql_file_flags_comment_only_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("leftover.sql");
+
+        fs::write(&file_path, "-- placeholder\n/* removed in merge */\n").unwrap();
+
+        let (_, notices) =
+            load_sql_file(&file_path, &Reader::new(&ReadLimits::default()).unwrap()).unwrap();
+
+        assert_eq!(notices.len(), 1);
+        assert!(notices[0].contains("comment-only"));
+    }
 }
@@ -0,0 +1,380 @@
+//! Content-defined chunking of built schemas for delta uploads
+//!
+//! Splits bytes at positions chosen by a rolling gear hash (FastCDC with
+//! normalized chunking), so an edit only changes the chunks around it:
+//! boundaries before the edit stay put and those after it resynchronize
+//! within a chunk or two. An artifact store keyed by chunk SHA256 then only
+//! needs the chunks it has not seen.
+//!
+//! `write_content_chunks` stores each chunk as `<out_dir>/<sha256>` (skipping
+//! chunks already present, so `out_dir` can be a shared store) and writes a
+//! `MANIFEST-<schema_sha256>` listing them in order:
+//!
+//! ```text
+//! format: confiture-cdc/1
+//! schema_sha256: <SHA256 of the whole input>
+//! min_size: 16384
+//! avg_size: 65536
+//! max_size: 262144
+//! chunks:
+//! <SHA256>  <length>
+//! ```
+//!
+//! Every file is written to a temporary name and renamed into place, so an
+//! interrupted run never leaves a truncated chunk under its hash; an existing
+//! chunk is re-hashed before it is trusted. Manifests are named by schema
+//! digest, so builds sharing a store do not overwrite each other's.
+//!
+//! The gear table and cut rule are part of the format: changing either moves
+//! every boundary, so bump the format version if they ever change.
+
+#![allow(clippy::useless_conversion)]
+
+use crate::errors::{CoreError, ErrorCode};
+use crate::package::sha256_hex;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::fs;
+use std::path::Path;
+
+const FORMAT: &str = "confiture-cdc/1";
+const MANIFEST_PREFIX: &str = "MANIFEST-";
+
+/// Smallest `min_size` accepted: the gear hash looks at the last 64 bytes
+const MIN_CHUNK_FLOOR: usize = 64;
+
+/// 256 pseudo-random values (splitmix64 from a fixed seed), one per byte
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x636f_6e66_6974_7572; // "confitur"
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+/// One content-defined chunk of the input
+#[cfg_attr(feature = "python", pyclass(get_all, frozen))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentChunk {
+    /// Byte offset in the input
+    pub offset: usize,
+    pub length: usize,
+    pub sha256: String,
+}
+
+/// Chunk size bounds, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkSizes {
+    pub min: usize,
+    pub avg: usize,
+    pub max: usize,
+}
+
+impl Default for ChunkSizes {
+    fn default() -> Self {
+        Self {
+            min: 16 * 1024,
+            avg: 64 * 1024,
+            max: 256 * 1024,
+        }
+    }
+}
+
+/// Split a built schema into content-defined chunks and store them
+///
+/// Args:
+///     schema: Output of `build_schema`
+///     out_dir: Chunk store directory (created if missing); chunks are
+///         written as `<sha256>` unless already present, plus a
+///         `MANIFEST-<sha256 of schema>`
+///     min_size: Smallest chunk in bytes, except the last (at least 64)
+///     avg_size: Target average chunk size in bytes
+///     max_size: Largest chunk in bytes
+///
+/// Returns:
+///     List of ContentChunk in input order
+///
+/// Raises:
+///     NativeError: code `SCHEMA_206` unless
+///         `64 <= min_size <= avg_size <= max_size`, or `SCHEMA_209` if a
+///         file cannot be written
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (schema, out_dir, min_size = 16_384, avg_size = 65_536, max_size = 262_144))]
+pub fn write_content_chunks(
+    schema: &str,
+    out_dir: String,
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+) -> PyResult<Vec<ContentChunk>> {
+    let sizes = ChunkSizes {
+        min: min_size,
+        avg: avg_size,
+        max: max_size,
+    };
+    Ok(write_cdc_chunks(
+        schema.as_bytes(),
+        Path::new(&out_dir),
+        sizes,
+    )?)
+}
+
+/// Chunk `data` and write the chunk store and manifest (pure Rust core of
+/// `write_content_chunks`)
+pub fn write_cdc_chunks(
+    data: &[u8],
+    out_dir: &Path,
+    sizes: ChunkSizes,
+) -> Result<Vec<ContentChunk>, CoreError> {
+    let chunks = content_chunks(data, sizes)?;
+
+    fs::create_dir_all(out_dir).map_err(|e| write_error(out_dir, e))?;
+    for chunk in &chunks {
+        let path = out_dir.join(&chunk.sha256);
+        // Content-addressed: an existing file holding the right bytes is kept
+        let stored = fs::read(&path).is_ok_and(|bytes| sha256_hex(&bytes) == chunk.sha256);
+        if !stored {
+            write_atomically(&path, &data[chunk.offset..chunk.offset + chunk.length])
+                .map_err(|e| write_error(&path, e))?;
+        }
+    }
+
+    let schema_sha256 = sha256_hex(data);
+    let path = out_dir.join(format!("{}{}", MANIFEST_PREFIX, schema_sha256));
+    write_atomically(
+        &path,
+        render_manifest(&schema_sha256, sizes, &chunks).as_bytes(),
+    )
+    .map_err(|e| write_error(&path, e))?;
+
+    Ok(chunks)
+}
+
+/// Write through a temporary file in the same directory, then rename, so
+/// `path` is either absent or complete; the process id keeps concurrent
+/// writers to a shared store apart
+fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// Split `data` into content-defined chunks
+pub fn content_chunks(data: &[u8], sizes: ChunkSizes) -> Result<Vec<ContentChunk>, CoreError> {
+    validate(sizes)?;
+
+    let mut chunks = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let length = cut_point(&data[offset..], sizes);
+        chunks.push(ContentChunk {
+            offset,
+            length,
+            sha256: sha256_hex(&data[offset..offset + length]),
+        });
+        offset += length;
+    }
+    Ok(chunks)
+}
+
+/// Length of the next chunk at the start of `data`
+///
+/// Normalized chunking: below `avg` a boundary needs two more zero bits than
+/// the target, above it two fewer, which keeps sizes close to `avg`.
+fn cut_point(data: &[u8], sizes: ChunkSizes) -> usize {
+    if data.len() <= sizes.min {
+        return data.len();
+    }
+    let end = data.len().min(sizes.max);
+    let normal = end.min(sizes.avg);
+
+    let bits = sizes.avg.ilog2();
+    let strict_mask = top_bits_mask(bits + 2);
+    let loose_mask = top_bits_mask(bits.saturating_sub(2).max(1));
+
+    let mut hash: u64 = 0;
+    for (i, &byte) in data.iter().enumerate().take(end).skip(sizes.min) {
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        let mask = if i < normal { strict_mask } else { loose_mask };
+        if hash & mask == 0 {
+            return i + 1;
+        }
+    }
+    end
+}
+
+/// Mask of the `bits` highest bits, which depend on the last 64 bytes
+fn top_bits_mask(bits: u32) -> u64 {
+    !0u64 << (64 - bits.min(63))
+}
+
+fn validate(sizes: ChunkSizes) -> Result<(), CoreError> {
+    let checks = [
+        ("min_size", sizes.min >= MIN_CHUNK_FLOOR, "at least 64"),
+        ("avg_size", sizes.avg >= sizes.min, "at least min_size"),
+        ("max_size", sizes.max >= sizes.avg, "at least avg_size"),
+    ];
+    for (option, ok, rule) in checks {
+        if !ok {
+            return Err(CoreError::new(
+                ErrorCode::InvalidOption,
+                format!("{} must be {}", option, rule),
+            )
+            .with_detail("option", option));
+        }
+    }
+    Ok(())
+}
+
+fn render_manifest(schema_sha256: &str, sizes: ChunkSizes, chunks: &[ContentChunk]) -> String {
+    let mut text = format!(
+        "format: {}\nschema_sha256: {}\nmin_size: {}\navg_size: {}\nmax_size: {}\nchunks:\n",
+        FORMAT, schema_sha256, sizes.min, sizes.avg, sizes.max
+    );
+    for chunk in chunks {
+        text.push_str(&format!("{}  {}\n", chunk.sha256, chunk.length));
+    }
+    text
+}
+
+fn write_error(path: &Path, e: std::io::Error) -> CoreError {
+    CoreError::new(
+        ErrorCode::FileWrite,
+        format!("Cannot write schema chunk {}: {}", path.display(), e),
+    )
+    .with_detail("file", path.display().to_string())
+    .with_detail("reason", e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use tempfile::TempDir;
+
+    const SIZES: ChunkSizes = ChunkSizes {
+        min: 256,
+        avg: 1024,
+        max: 4096,
+    };
+
+    /// Deterministic schema-like text with varied content
+    fn schema(tables: usize) -> String {
+        (0..tables)
+            .map(|i| {
+                format!(
+                    "CREATE TABLE t_{i} (id BIGINT PRIMARY KEY, c_{} TEXT NOT NULL);\n",
+                    i * 7919 % 1000
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_content_chunks_cover_input_within_bounds() {
+        let data = schema(2000);
+        let chunks = content_chunks(data.as_bytes(), SIZES).unwrap();
+
+        let mut offset = 0;
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.offset, offset);
+            assert!(chunk.length <= SIZES.max);
+            assert!(chunk.length >= SIZES.min || i == chunks.len() - 1);
+            offset += chunk.length;
+        }
+        assert_eq!(offset, data.len());
+        assert!(chunks.len() > data.len() / SIZES.max);
+    }
+
+    #[test]
+    fn test_content_chunks_resync_after_edit() {
+        let original = schema(2000);
+        let edited = original.replacen("t_1000 (", "t_1000 (note TEXT, ", 1);
+
+        let before: HashSet<String> = content_chunks(original.as_bytes(), SIZES)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.sha256)
+            .collect();
+        let after = content_chunks(edited.as_bytes(), SIZES).unwrap();
+        let changed = after.iter().filter(|c| !before.contains(&c.sha256)).count();
+
+        assert!(
+            changed <= 3,
+            "{} of {} chunks changed",
+            changed,
+            after.len()
+        );
+    }
+
+    #[test]
+    fn test_write_cdc_chunks_stores_chunks_and_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = schema(500);
+
+        let chunks = write_cdc_chunks(data.as_bytes(), temp_dir.path(), SIZES).unwrap();
+
+        let rebuilt: Vec<u8> = chunks
+            .iter()
+            .flat_map(|c| fs::read(temp_dir.path().join(&c.sha256)).unwrap())
+            .collect();
+        assert_eq!(rebuilt, data.as_bytes());
+        let manifest_name = format!("MANIFEST-{}", sha256_hex(data.as_bytes()));
+        let manifest = fs::read_to_string(temp_dir.path().join(manifest_name)).unwrap();
+        assert!(manifest.starts_with("format: confiture-cdc/1\n"));
+        assert_eq!(manifest.lines().count(), 6 + chunks.len());
+        // Only chunks and the manifest, no temporary files
+        assert_eq!(
+            fs::read_dir(temp_dir.path()).unwrap().count(),
+            chunks
+                .iter()
+                .map(|c| &c.sha256)
+                .collect::<HashSet<_>>()
+                .len()
+                + 1
+        );
+    }
+
+    #[test]
+    fn test_write_cdc_chunks_repairs_truncated_chunk_and_keeps_manifests() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = schema(500);
+        let second = schema(600);
+
+        let chunks = write_cdc_chunks(first.as_bytes(), temp_dir.path(), SIZES).unwrap();
+        // As left behind by an interrupted, non-atomic writer
+        let damaged = temp_dir.path().join(&chunks[0].sha256);
+        fs::write(&damaged, &first.as_bytes()[..10]).unwrap();
+        write_cdc_chunks(first.as_bytes(), temp_dir.path(), SIZES).unwrap();
+        write_cdc_chunks(second.as_bytes(), temp_dir.path(), SIZES).unwrap();
+
+        assert_eq!(
+            fs::read(&damaged).unwrap(),
+            &first.as_bytes()[..chunks[0].length]
+        );
+        for data in [&first, &second] {
+            let manifest_name = format!("MANIFEST-{}", sha256_hex(data.as_bytes()));
+            assert!(temp_dir.path().join(manifest_name).is_file());
+        }
+    }
+
+    #[test]
+    fn test_content_chunks_rejects_bad_sizes() {
+        let sizes = ChunkSizes { min: 32, ..SIZES };
+        let err = content_chunks(b"x", sizes).unwrap_err();
+
+        assert_eq!(err.code, ErrorCode::InvalidOption);
+        assert!(err.details.contains(&("option", "min_size".to_string())));
+    }
+}
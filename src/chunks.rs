@@ -0,0 +1,471 @@
+//! Splitting built schemas into numbered chunk files
+//!
+//! Some deployment tooling and managed-PostgreSQL query size limits cannot
+//! take one very large SQL file. `write_schema_chunks` splits a built schema
+//! into `0001.sql`, `0002.sql`, ... at statement boundaries (never inside a
+//! string, quoted identifier or dollar-quoted body, see `crate::sql`) and
+//! writes a `MANIFEST` listing them in apply order:
+//!
+//! ```text
+//! format: confiture-chunks/1
+//! schema_sha256: <SHA256 of the whole schema>
+//! chunks:
+//! <SHA256>  <statement count>  0001.sql
+//! <SHA256>  <statement count>  0002.sql
+//! ```
+//!
+//! Concatenating the chunks in manifest order reproduces the schema byte for
+//! byte. Chunk files listed by the previous `MANIFEST` but not by the new one
+//! (left from an earlier, longer split) are removed, so globbing `*.sql` in
+//! the directory yields exactly the new chunks; other files are never touched.
+//! `verify_chunks` re-checks a directory against its manifest before apply.
+
+#![allow(clippy::useless_conversion)]
+
+#[cfg(feature = "python")]
+use crate::errors::warn_all;
+use crate::errors::{CoreError, ErrorCode};
+use crate::hasher::{read_error, read_file};
+use crate::package::sha256_hex;
+use crate::sql::split_statements;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::fs;
+use std::path::Path;
+
+const FORMAT: &str = "confiture-chunks/1";
+const MANIFEST_FILE: &str = "MANIFEST";
+
+/// One chunk file written by `write_schema_chunks`
+#[cfg_attr(feature = "python", pyclass(get_all, frozen))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaChunk {
+    /// File name inside the output directory, e.g. "0001.sql"
+    pub file: String,
+    /// Statements in the chunk
+    pub statements: usize,
+    pub bytes: usize,
+    pub sha256: String,
+}
+
+/// Split a built schema into chunk files at statement boundaries
+///
+/// Args:
+///     schema: Output of `build_schema`
+///     out_dir: Directory for the chunk files and `MANIFEST` (created if
+///         missing); chunks listed only by its previous `MANIFEST` are
+///         removed
+///     max_statements: Most statements per chunk
+///     max_bytes: Most bytes per chunk
+///
+/// Returns:
+///     List of SchemaChunk in apply order
+///
+/// Raises:
+///     NativeError: code `SCHEMA_206` if neither limit is given or a limit
+///         is 0, or `SCHEMA_209` if a file cannot be written
+///
+/// Warns:
+///     NativeWarning: for each statement larger than `max_bytes`, which gets
+///         a chunk of its own, and if an existing `MANIFEST` cannot be read
+///         (stale chunks are then left in place)
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (schema, out_dir, max_statements = None, max_bytes = None))]
+pub fn write_schema_chunks(
+    schema: &str,
+    out_dir: String,
+    max_statements: Option<usize>,
+    max_bytes: Option<usize>,
+) -> PyResult<Vec<SchemaChunk>> {
+    let (chunks, notices) = write_chunks(schema, Path::new(&out_dir), max_statements, max_bytes)?;
+    warn_all(notices)?;
+    Ok(chunks)
+}
+
+/// Split `schema` and write the chunks and manifest to `out_dir` (pure Rust
+/// core of `write_schema_chunks`)
+///
+/// Returns the chunks and one notice per oversized statement.
+pub fn write_chunks(
+    schema: &str,
+    out_dir: &Path,
+    max_statements: Option<usize>,
+    max_bytes: Option<usize>,
+) -> Result<(Vec<SchemaChunk>, Vec<String>), CoreError> {
+    for (option, limit) in [("max_statements", max_statements), ("max_bytes", max_bytes)] {
+        if limit == Some(0) {
+            return Err(CoreError::new(
+                ErrorCode::InvalidOption,
+                format!("{} must be greater than 0", option),
+            )
+            .with_detail("option", option));
+        }
+    }
+    if max_statements.is_none() && max_bytes.is_none() {
+        return Err(CoreError::new(
+            ErrorCode::InvalidOption,
+            "Pass max_statements, max_bytes or both",
+        )
+        .with_detail("option", "max_statements"));
+    }
+
+    let (pieces, mut notices) = split_chunks(
+        schema,
+        max_statements.unwrap_or(usize::MAX),
+        max_bytes.unwrap_or(usize::MAX),
+    );
+
+    // Read before MANIFEST is overwritten below
+    let previous = previous_chunk_files(out_dir).unwrap_or_else(|reason| {
+        notices.push(format!(
+            "Existing MANIFEST in {} is not usable ({}); stale chunks are left in place",
+            out_dir.display(),
+            reason
+        ));
+        Vec::new()
+    });
+
+    fs::create_dir_all(out_dir).map_err(|e| write_error(out_dir, e))?;
+    let width = pieces.len().to_string().len().max(4);
+    let mut chunks = Vec::with_capacity(pieces.len());
+    for (i, (text, statements)) in pieces.iter().enumerate() {
+        let file = format!("{:0width$}.sql", i + 1, width = width);
+        let path = out_dir.join(&file);
+        fs::write(&path, text).map_err(|e| write_error(&path, e))?;
+        chunks.push(SchemaChunk {
+            file,
+            statements: *statements,
+            bytes: text.len(),
+            sha256: sha256_hex(text.as_bytes()),
+        });
+    }
+
+    let path = out_dir.join(MANIFEST_FILE);
+    fs::write(&path, render_manifest(schema, &chunks)).map_err(|e| write_error(&path, e))?;
+    remove_stale_chunks(out_dir, &previous, &chunks)?;
+
+    Ok((chunks, notices))
+}
+
+/// Chunk files listed by the `MANIFEST` already in `out_dir`, if any
+fn previous_chunk_files(out_dir: &Path) -> Result<Vec<String>, String> {
+    let text = match fs::read_to_string(out_dir.join(MANIFEST_FILE)) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let (_, chunks) = parse_manifest(&text)?;
+    Ok(chunks.into_iter().map(|chunk| chunk.file).collect())
+}
+
+/// Delete the `previous` chunk files not among `chunks`
+fn remove_stale_chunks(
+    out_dir: &Path,
+    previous: &[String],
+    chunks: &[SchemaChunk],
+) -> Result<(), CoreError> {
+    for name in previous {
+        if chunks.iter().any(|chunk| &chunk.file == name) {
+            continue;
+        }
+        let path = out_dir.join(name);
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(write_error(&path, e))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Check a chunk directory against its `MANIFEST`
+///
+/// Re-hashes every listed chunk and the schema they concatenate to, so a
+/// truncated upload or a hand-edited chunk is caught before apply. Returns
+/// the manifest's `schema_sha256` and the chunks in apply order.
+pub fn verify_chunks(dir: &Path) -> Result<(String, Vec<SchemaChunk>), CoreError> {
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let text = fs::read_to_string(&manifest_path).map_err(|e| read_error(&manifest_path, e))?;
+    let (schema_sha256, listed) =
+        parse_manifest(&text).map_err(|reason| verify_error(&manifest_path, reason))?;
+
+    let mut schema = Vec::new();
+    let mut chunks = Vec::with_capacity(listed.len());
+    for mut chunk in listed {
+        let path = dir.join(&chunk.file);
+        let bytes = read_file(&path)?;
+        if sha256_hex(&bytes) != chunk.sha256 {
+            return Err(verify_error(&path, "SHA256 does not match MANIFEST"));
+        }
+        chunk.bytes = bytes.len();
+        schema.extend_from_slice(&bytes);
+        chunks.push(chunk);
+    }
+    if sha256_hex(&schema) != schema_sha256 {
+        return Err(verify_error(
+            &manifest_path,
+            "chunks do not concatenate to schema_sha256",
+        ));
+    }
+
+    Ok((schema_sha256, chunks))
+}
+
+/// Cut `schema` into `(text, statement count)` pieces within the limits
+///
+/// Cuts fall just after a statement's `;` and the rest of its line, so
+/// comments and `-- File:` headers travel with the statement that follows
+/// them. Text after the last statement joins the last piece.
+fn split_chunks(
+    schema: &str,
+    max_statements: usize,
+    max_bytes: usize,
+) -> (Vec<(&str, usize)>, Vec<String>) {
+    let mut pieces = Vec::new();
+    let mut notices = Vec::new();
+    let mut start = 0;
+    let mut cut = 0;
+    let mut count = 0;
+
+    for statement in split_statements(schema) {
+        let end = line_end(schema, statement.end);
+        if count > 0 && (count == max_statements || end - start > max_bytes) {
+            pieces.push((&schema[start..cut], count));
+            start = cut;
+            count = 0;
+        }
+        if end - start > max_bytes {
+            notices.push(format!(
+                "Statement at line {} is {} bytes, over max_bytes ({}); it gets its own chunk",
+                statement.line,
+                end - start,
+                max_bytes
+            ));
+        }
+        cut = end;
+        count += 1;
+    }
+
+    if start < schema.len() {
+        pieces.push((&schema[start..], count));
+    }
+    (pieces, notices)
+}
+
+/// Offset just past the line containing `offset - 1`, if only whitespace
+/// follows on it; `offset` otherwise
+fn line_end(text: &str, offset: usize) -> usize {
+    let rest = &text[offset..];
+    match rest.find('\n') {
+        Some(newline) if rest[..newline].trim().is_empty() => offset + newline + 1,
+        None if rest.trim().is_empty() => text.len(),
+        _ => offset,
+    }
+}
+
+fn render_manifest(schema: &str, chunks: &[SchemaChunk]) -> String {
+    let mut text = format!(
+        "format: {}\nschema_sha256: {}\nchunks:\n",
+        FORMAT,
+        sha256_hex(schema.as_bytes())
+    );
+    for chunk in chunks {
+        text.push_str(&format!(
+            "{}  {}  {}\n",
+            chunk.sha256, chunk.statements, chunk.file
+        ));
+    }
+    text
+}
+
+/// Parse a `MANIFEST` into the schema digest and the listed chunks (with
+/// `bytes` left at 0)
+fn parse_manifest(text: &str) -> Result<(String, Vec<SchemaChunk>), String> {
+    let mut lines = text.lines();
+    let mut field = |name: &str| {
+        lines
+            .next()
+            .and_then(|line| line.strip_prefix(name))
+            .and_then(|rest| rest.strip_prefix(": ").or(rest.strip_prefix(":")))
+            .map(str::to_string)
+            .ok_or_else(|| format!("MANIFEST is missing '{}'", name))
+    };
+
+    let format = field("format")?;
+    if format != FORMAT {
+        return Err(format!("unsupported chunk format '{}'", format));
+    }
+    let schema_sha256 = field("schema_sha256")?;
+    field("chunks")?;
+
+    let chunks = lines
+        .map(|line| {
+            let mut parts = line.splitn(3, "  ");
+            match (parts.next(), parts.next().map(str::parse), parts.next()) {
+                (Some(sha256), Some(Ok(statements)), Some(file)) if is_chunk_name(file) => {
+                    Ok(SchemaChunk {
+                        file: file.to_string(),
+                        statements,
+                        bytes: 0,
+                        sha256: sha256.to_string(),
+                    })
+                }
+                _ => Err(format!("malformed MANIFEST line '{}'", line)),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((schema_sha256, chunks))
+}
+
+/// True for the numbered file names `write_chunks` produces (`0007.sql`)
+fn is_chunk_name(name: &str) -> bool {
+    name.strip_suffix(".sql")
+        .is_some_and(|stem| !stem.is_empty() && stem.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn verify_error(path: &Path, reason: impl Into<String>) -> CoreError {
+    let reason = reason.into();
+    CoreError::new(
+        ErrorCode::InvalidPackage,
+        format!(
+            "Schema chunk {} failed verification: {}",
+            path.display(),
+            reason
+        ),
+    )
+    .with_detail("file", path.display().to_string())
+    .with_detail("reason", reason)
+}
+
+fn write_error(path: &Path, e: std::io::Error) -> CoreError {
+    CoreError::new(
+        ErrorCode::FileWrite,
+        format!("Cannot write schema chunk {}: {}", path.display(), e),
+    )
+    .with_detail("file", path.display().to_string())
+    .with_detail("reason", e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SCHEMA: &str = "\
+-- File: 01.sql
+CREATE TABLE a (id INT);
+CREATE FUNCTION f() RETURNS int AS $$
+BEGIN
+  RETURN 1;
+END;
+$$ LANGUAGE plpgsql;
+-- File: 02.sql
+CREATE TABLE b (id INT);
+";
+
+    fn joined(pieces: &[(&str, usize)]) -> String {
+        pieces.iter().map(|(text, _)| *text).collect()
+    }
+
+    #[test]
+    fn test_split_chunks_by_statement_count() {
+        let (pieces, notices) = split_chunks(SCHEMA, 2, usize::MAX);
+
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces[0].0.ends_with("$$ LANGUAGE plpgsql;\n"));
+        assert!(pieces[1].0.starts_with("-- File: 02.sql\n"));
+        assert_eq!((pieces[0].1, pieces[1].1), (2, 1));
+        assert_eq!(joined(&pieces), SCHEMA);
+        assert!(notices.is_empty());
+    }
+
+    #[test]
+    fn test_split_chunks_by_size_keeps_dollar_bodies_whole() {
+        let (pieces, notices) = split_chunks(SCHEMA, usize::MAX, 45);
+
+        assert_eq!(pieces.len(), 3);
+        assert!(pieces[1].0.contains("RETURN 1;\nEND;\n$$"));
+        assert_eq!(joined(&pieces), SCHEMA);
+        // The function alone is over 45 bytes
+        assert_eq!(notices.len(), 1);
+        assert!(notices[0].contains("line 3"));
+    }
+
+    #[test]
+    fn test_write_chunks_writes_files_and_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        let out_dir = temp_dir.path().join("chunks");
+
+        let (chunks, _) = write_chunks(SCHEMA, &out_dir, Some(2), None).unwrap();
+
+        assert_eq!(chunks[0].file, "0001.sql");
+        let rebuilt: String = chunks
+            .iter()
+            .map(|c| fs::read_to_string(out_dir.join(&c.file)).unwrap())
+            .collect();
+        assert_eq!(rebuilt, SCHEMA);
+        let manifest = fs::read_to_string(out_dir.join(MANIFEST_FILE)).unwrap();
+        assert!(manifest.starts_with("format: confiture-chunks/1\n"));
+        assert!(manifest.ends_with(&format!("{}  1  0002.sql\n", chunks[1].sha256)));
+
+        let err = write_chunks(SCHEMA, &out_dir, None, None).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidOption);
+    }
+
+    #[test]
+    fn test_write_chunks_removes_stale_chunks() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("notes.sql"), "-- keep\n").unwrap();
+        // Numbered, but not written by a previous split
+        fs::write(temp_dir.path().join("2024.sql"), "-- keep\n").unwrap();
+
+        write_chunks(SCHEMA, temp_dir.path(), Some(1), None).unwrap();
+        let (chunks, _) = write_chunks(SCHEMA, temp_dir.path(), Some(2), None).unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(
+            names,
+            vec!["0001.sql", "0002.sql", "2024.sql", "MANIFEST", "notes.sql"]
+        );
+    }
+
+    #[test]
+    fn test_write_chunks_keeps_files_under_foreign_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(MANIFEST_FILE), "release notes\n").unwrap();
+        fs::write(temp_dir.path().join("0009.sql"), "-- keep\n").unwrap();
+
+        let (_, notices) = write_chunks(SCHEMA, temp_dir.path(), Some(2), None).unwrap();
+
+        assert!(temp_dir.path().join("0009.sql").exists());
+        assert_eq!(notices.len(), 1);
+        assert!(notices[0].contains("stale chunks are left in place"));
+    }
+
+    #[test]
+    fn test_verify_chunks_detects_tampering() {
+        let temp_dir = TempDir::new().unwrap();
+        let (written, _) = write_chunks(SCHEMA, temp_dir.path(), Some(2), None).unwrap();
+
+        let (schema_sha256, chunks) = verify_chunks(temp_dir.path()).unwrap();
+        assert_eq!(schema_sha256, sha256_hex(SCHEMA.as_bytes()));
+        assert_eq!(chunks, written);
+
+        fs::write(temp_dir.path().join("0002.sql"), "DROP TABLE b;\n").unwrap();
+        let err = verify_chunks(temp_dir.path()).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidPackage);
+        assert!(err.message.contains("0002.sql"));
+
+        fs::remove_file(temp_dir.path().join("0002.sql")).unwrap();
+        let err = verify_chunks(temp_dir.path()).unwrap_err();
+        assert_eq!(err.code, ErrorCode::FileRead);
+    }
+}
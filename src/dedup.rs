@@ -0,0 +1,201 @@
+//! Duplicate statement report across a schema tree
+//!
+//! Finds statements that appear more than once after normalization
+//! (comments dropped, whitespace collapsed): duplicated indexes, repeated
+//! grants, copy-pasted functions. These apply cleanly in review but fail with
+//! "already exists" once the tree is built into one script.
+//!
+//! Session and transaction control (`SET`, `RESET`, `BEGIN`, `COMMIT`, ...)
+//! legitimately repeats from file to file and is not reported.
+
+#![allow(clippy::useless_conversion)]
+
+use crate::builder::find_common_parent;
+use crate::errors::CoreError;
+use crate::hasher::read_file;
+use crate::sql::split_statements;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Leading keywords of statements that are expected to repeat
+const REPEATABLE_KEYWORDS: &[&str] = &[
+    "SET",
+    "RESET",
+    "BEGIN",
+    "START",
+    "COMMIT",
+    "END",
+    "ROLLBACK",
+    "SAVEPOINT",
+    "RELEASE",
+];
+
+/// Where a statement occurs
+#[cfg_attr(feature = "python", pyclass(get_all, frozen))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementLocation {
+    /// File path relative to `base_dir`
+    pub file: String,
+    /// 1-based line the statement starts on
+    pub line: usize,
+}
+
+/// A normalized statement and every place it occurs (two or more)
+#[cfg_attr(feature = "python", pyclass(get_all, frozen))]
+#[derive(Debug, Clone)]
+pub struct DuplicateStatement {
+    /// Normalized statement text, without the trailing `;`
+    pub statement: String,
+    /// Occurrences in file order, then line order
+    pub locations: Vec<StatementLocation>,
+}
+
+/// Report statements that occur more than once across files
+///
+/// Args:
+///     files: List of SQL file paths, in build order
+///     base_dir: Directory that reported paths are relative to
+///         (default: common parent of `files`)
+///
+/// Returns:
+///     List of DuplicateStatement, ordered by first occurrence
+///
+/// Raises:
+///     NativeError: code `SCHEMA_205` if a file cannot be opened or read
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (files, base_dir = None))]
+pub fn find_duplicate_statements(
+    files: Vec<String>,
+    base_dir: Option<String>,
+) -> PyResult<Vec<DuplicateStatement>> {
+    Ok(find_duplicates(&files, base_dir)?)
+}
+
+/// Group identical normalized statements across files (pure Rust core of
+/// `find_duplicate_statements`)
+pub fn find_duplicates(
+    files: &[String],
+    base_dir: Option<String>,
+) -> Result<Vec<DuplicateStatement>, CoreError> {
+    let paths: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+    let base_dir = base_dir
+        .map(PathBuf::from)
+        .unwrap_or_else(|| find_common_parent(&paths));
+
+    // Read and split in parallel (collect preserves input order)
+    let per_file: Vec<_> = paths
+        .par_iter()
+        .map(|path| {
+            let content = read_file(path)?;
+            let text = String::from_utf8_lossy(&content);
+            Ok(split_statements(
+                text.strip_prefix('\u{feff}').unwrap_or(&text),
+            ))
+        })
+        .collect::<Result<_, CoreError>>()?;
+
+    // Group by text, remembering first-occurrence order
+    let mut groups: Vec<DuplicateStatement> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (path, statements) in paths.iter().zip(per_file) {
+        let file = path
+            .strip_prefix(&base_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned();
+
+        for statement in statements {
+            if is_repeatable(&statement.text) {
+                continue;
+            }
+            let location = StatementLocation {
+                file: file.clone(),
+                line: statement.line,
+            };
+            match index.get(&statement.text) {
+                Some(&i) => groups[i].locations.push(location),
+                None => {
+                    index.insert(statement.text.clone(), groups.len());
+                    groups.push(DuplicateStatement {
+                        statement: statement.text,
+                        locations: vec![location],
+                    });
+                }
+            }
+        }
+    }
+
+    groups.retain(|group| group.locations.len() > 1);
+    Ok(groups)
+}
+
+fn is_repeatable(statement: &str) -> bool {
+    let keyword = statement
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or("");
+    REPEATABLE_KEYWORDS
+        .iter()
+        .any(|k| k.eq_ignore_ascii_case(keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorCode;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_duplicates_reports_all_locations() {
+        let temp_dir = TempDir::new().unwrap();
+        let tables = temp_dir.path().join("10_tables.sql");
+        let indexes = temp_dir.path().join("20_indexes.sql");
+
+        fs::write(
+            &tables,
+            "SET search_path = crm;\nCREATE TABLE t (a INT);\nCREATE INDEX idx_a ON t (a);\n",
+        )
+        .unwrap();
+        fs::write(
+            &indexes,
+            "SET search_path = crm;\n-- moved here\nCREATE INDEX idx_a\n    ON t (a);\nGRANT SELECT ON t TO app;\nGRANT SELECT ON t TO app;\n",
+        )
+        .unwrap();
+
+        let files = vec![
+            tables.to_str().unwrap().to_string(),
+            indexes.to_str().unwrap().to_string(),
+        ];
+        let duplicates = find_duplicates(&files, None).unwrap();
+
+        assert_eq!(duplicates.len(), 2);
+        assert_eq!(duplicates[0].statement, "CREATE INDEX idx_a ON t (a)");
+        assert_eq!(
+            duplicates[0].locations,
+            vec![
+                StatementLocation {
+                    file: "10_tables.sql".to_string(),
+                    line: 3,
+                },
+                StatementLocation {
+                    file: "20_indexes.sql".to_string(),
+                    line: 3,
+                },
+            ]
+        );
+        assert_eq!(duplicates[1].statement, "GRANT SELECT ON t TO app");
+        assert_eq!(duplicates[1].locations.len(), 2);
+    }
+
+    #[test]
+    fn test_find_duplicates_missing_file_returns_error_code() {
+        let err = find_duplicates(&["/nonexistent/schema.sql".to_string()], None).unwrap_err();
+
+        assert_eq!(err.code, ErrorCode::FileRead);
+    }
+}
@@ -0,0 +1,157 @@
+//! Structured errors and warnings for the Rust extension
+//!
+//! Every exception raised by `confiture._core` is a `NativeError` carrying a
+//! stable `code` attribute (registered in `confiture.core.error_codes`) and a
+//! `details` dict, so callers can branch on the failure without parsing the
+//! message text.
+//!
+//! Recoverable oddities are reported through Python's `warnings` module with
+//! the `NativeWarning` category, so CI can escalate them selectively with
+//! `-W error::confiture._core.NativeWarning`.
+
+#[cfg(feature = "python")]
+use pyo3::create_exception;
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyException, PyUserWarning};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+#[cfg(feature = "python")]
+use std::ffi::CString;
+use std::fmt;
+
+#[cfg(feature = "python")]
+create_exception!(
+    confiture._core,
+    NativeError,
+    PyException,
+    "Error raised by the confiture Rust extension (see `code` and `details`)."
+);
+
+#[cfg(feature = "python")]
+create_exception!(
+    confiture._core,
+    NativeWarning,
+    PyUserWarning,
+    "Non-fatal issue found by the confiture Rust extension."
+);
+
+/// Stable error codes raised from Rust
+///
+/// Codes are public API: keep them in sync with
+/// `python/confiture/core/error_codes.py` and never renumber them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// A schema file could not be opened or read
+    FileRead,
+    /// An argument passed to a native function is out of range
+    InvalidOption,
+    /// A pg_dump archive is malformed or in an unsupported format
+    InvalidArchive,
+    /// Schema files hold only whitespace or comments (strict mode)
+    EmptyFile,
+    /// An output file (e.g. a schema package) could not be written
+    FileWrite,
+    /// A schema package or chunk directory is malformed or fails its
+    /// integrity check
+    InvalidPackage,
+    /// A file passed to `read_status` is not an operation status file
+    InvalidStatus,
+}
+
+impl ErrorCode {
+    /// Symbolic code exposed to Python as `NativeError.code`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::FileRead => "SCHEMA_205",
+            ErrorCode::InvalidOption => "SCHEMA_206",
+            ErrorCode::InvalidArchive => "SCHEMA_207",
+            ErrorCode::EmptyFile => "SCHEMA_208",
+            ErrorCode::FileWrite => "SCHEMA_209",
+            ErrorCode::InvalidPackage => "SCHEMA_210",
+            ErrorCode::InvalidStatus => "SCHEMA_211",
+        }
+    }
+
+    /// Process exit code per `CANONICAL_EXIT_CODES` (SCHEMA family → 4)
+    pub fn exit_code(self) -> u8 {
+        4
+    }
+}
+
+/// Error produced by the pure-Rust core, converted to `NativeError` at the
+/// Python boundary
+#[derive(Debug)]
+pub struct CoreError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Vec<(&'static str, String)>,
+}
+
+impl CoreError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: Vec::new(),
+        }
+    }
+
+    /// Attach a `details` entry (e.g. the offending file path)
+    pub fn with_detail(mut self, key: &'static str, value: impl Into<String>) -> Self {
+        self.details.push((key, value.into()));
+        self
+    }
+}
+
+impl fmt::Display for CoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.code.as_str(), self.message)
+    }
+}
+
+impl std::error::Error for CoreError {}
+
+#[cfg(feature = "python")]
+impl From<CoreError> for PyErr {
+    fn from(err: CoreError) -> Self {
+        Python::with_gil(|py| {
+            let py_err = NativeError::new_err(err.message);
+            let value = py_err.value(py);
+
+            let details = PyDict::new(py);
+            for (key, detail) in err.details {
+                // Inserting str keys into a fresh dict cannot fail
+                let _ = details.set_item(key, detail);
+            }
+
+            // Plain attribute assignment on a new exception instance
+            let _ = value.setattr("code", err.code.as_str());
+            let _ = value.setattr("details", details);
+
+            py_err
+        })
+    }
+}
+
+/// Emit each notice as a `NativeWarning`
+///
+/// Only acquires the GIL when there is something to report. Returns an error
+/// if a warnings filter escalates the warning to an exception.
+#[cfg(feature = "python")]
+pub fn warn_all(notices: Vec<String>) -> PyResult<()> {
+    if notices.is_empty() {
+        return Ok(());
+    }
+
+    Python::with_gil(|py| {
+        let category = py.get_type::<NativeWarning>();
+        for notice in notices {
+            // Paths and messages never contain NUL; drop any that would
+            let message = CString::new(notice.replace('\0', "")).unwrap_or_default();
+            PyErr::warn(py, &category, &message, 1)?;
+        }
+        Ok(())
+    })
+}
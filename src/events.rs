@@ -0,0 +1,284 @@
+//! Build progress as a stream of typed events
+//!
+//! An alternative to status files and callbacks for frontends that render
+//! progress themselves (e.g. a TUI): the build runs on a worker thread and
+//! the caller pulls `ProgressEvent`s from an iterator, in order:
+//!
+//! - `phase_started` (phase "reading")
+//! - `file_processed` for each file, in completion order
+//! - `warning` for each recoverable oddity (these replace `NativeWarning`s)
+//! - `finished` once the schema is assembled
+//!
+//! Migration applies run in Python, so there are no statement events here.
+
+#![allow(clippy::useless_conversion)]
+
+use crate::builder::concat_files_with_progress;
+use crate::errors::CoreError;
+use crate::limits::ReadLimits;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One step of a running build
+#[cfg_attr(feature = "python", pyclass(get_all, frozen))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressEvent {
+    /// "phase_started", "file_processed", "warning" or "finished"
+    pub kind: String,
+    /// Unix time the event occurred, in seconds
+    pub timestamp: f64,
+    /// Phase the build is in: "reading" or "done"
+    pub phase: String,
+    /// File just processed, relative to the base directory
+    pub file: Option<String>,
+    /// Warning text
+    pub message: Option<String>,
+    /// Files processed so far
+    pub done: usize,
+    pub total: usize,
+}
+
+impl ProgressEvent {
+    fn new(kind: &str, phase: &str, done: usize, total: usize) -> Self {
+        Self {
+            kind: kind.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0.0, |d| d.as_secs_f64()),
+            phase: phase.to_string(),
+            file: None,
+            message: None,
+            done,
+            total,
+        }
+    }
+}
+
+/// Build schema while streaming progress events
+///
+/// Args:
+///     files: List of SQL file paths to concatenate
+///     base_dir: Directory that `-- File:` headers are relative to
+///         (default: common parent of `files`)
+///     strict: Fail instead of warning on empty or comment-only files
+///     max_concurrent_reads: Files read at the same time (default: one per
+///         CPU)
+///     max_bytes_per_sec: Combined read rate limit (default: unlimited)
+///     read_retries: Extra attempts per file after a transient error such
+///         as EIO
+///
+/// Returns:
+///     Iterator of ProgressEvent; once exhausted, its `schema` attribute
+///     holds the same output as `build_schema(files, base_dir)`
+///
+/// Raises:
+///     NativeError: from `__next__`, code `SCHEMA_205` if a file cannot be
+///         read, `SCHEMA_206` if a read limit is 0, or `SCHEMA_208` if
+///         `strict` and any file is empty or comment-only
+///
+/// Warnings are delivered as `warning` events instead of `NativeWarning`s.
+/// The GIL is released while waiting for the next event.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    files,
+    base_dir = None,
+    strict = false,
+    max_concurrent_reads = None,
+    max_bytes_per_sec = None,
+    read_retries = 0,
+))]
+pub fn build_schema_events(
+    files: Vec<String>,
+    base_dir: Option<String>,
+    strict: bool,
+    max_concurrent_reads: Option<usize>,
+    max_bytes_per_sec: Option<u64>,
+    read_retries: u32,
+) -> BuildEvents {
+    let limits = ReadLimits {
+        max_concurrent_reads,
+        max_bytes_per_sec,
+        retries: read_retries,
+    };
+    BuildEvents::start(files, base_dir, strict, limits)
+}
+
+/// Event iterator behind `build_schema_events` (a Python iterator when the
+/// `python` feature is enabled)
+#[cfg_attr(feature = "python", pyclass)]
+pub struct BuildEvents {
+    // Only touched through `&mut self`; the mutex makes the pyclass `Sync`
+    events: Mutex<Receiver<ProgressEvent>>,
+    worker: Option<JoinHandle<Result<String, CoreError>>>,
+    schema: Option<String>,
+}
+
+impl BuildEvents {
+    /// Start the build on a worker thread
+    pub fn start(
+        files: Vec<String>,
+        base_dir: Option<String>,
+        strict: bool,
+        limits: ReadLimits,
+    ) -> Self {
+        let (sender, events) = channel();
+        let worker = thread::spawn(move || run_build(&files, base_dir, strict, &limits, &sender));
+
+        Self {
+            events: Mutex::new(events),
+            worker: Some(worker),
+            schema: None,
+        }
+    }
+
+    /// Next event, blocking until one arrives; `None` once the build has
+    /// finished, or the build's error if it failed
+    pub fn next_event(&mut self) -> Result<Option<ProgressEvent>, CoreError> {
+        let events = self.events.get_mut().unwrap_or_else(|e| e.into_inner());
+        if let Ok(event) = events.recv() {
+            return Ok(Some(event));
+        }
+
+        // The worker dropped its sender, so it has returned
+        if let Some(worker) = self.worker.take() {
+            let result = worker
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            self.schema = Some(result?);
+        }
+        Ok(None)
+    }
+
+    /// Built schema, available once the events are exhausted
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl BuildEvents {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<ProgressEvent>> {
+        Ok(py.allow_threads(|| self.next_event())?)
+    }
+
+    /// Built schema, or None until the iterator is exhausted
+    #[getter(schema)]
+    fn py_schema(&self) -> Option<String> {
+        self.schema.clone()
+    }
+}
+
+fn run_build(
+    files: &[String],
+    base_dir: Option<String>,
+    strict: bool,
+    limits: &ReadLimits,
+    sender: &Sender<ProgressEvent>,
+) -> Result<String, CoreError> {
+    let total = files.len();
+    // Send fails only once the iterator is dropped; the build still finishes
+    let send = |event: ProgressEvent| {
+        let _ = sender.send(event);
+    };
+
+    send(ProgressEvent::new("phase_started", "reading", 0, total));
+    // Counting, timestamping and sending under one lock keeps `done` and
+    // `timestamp` increasing in arrival order across rayon workers
+    let done = Mutex::new(0);
+    let (schema, notices) = concat_files_with_progress(files, base_dir, strict, limits, &|file| {
+        let mut done = done.lock().unwrap_or_else(|e| e.into_inner());
+        *done += 1;
+        send(ProgressEvent {
+            file: Some(file.to_string_lossy().into_owned()),
+            ..ProgressEvent::new("file_processed", "reading", *done, total)
+        });
+    })?;
+
+    for notice in notices {
+        send(ProgressEvent {
+            message: Some(notice),
+            ..ProgressEvent::new("warning", "reading", total, total)
+        });
+    }
+    send(ProgressEvent::new("finished", "done", total, total));
+
+    Ok(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::concat_files;
+    use crate::errors::ErrorCode;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn drain(events: &mut BuildEvents) -> Result<Vec<ProgressEvent>, CoreError> {
+        let mut all = Vec::new();
+        while let Some(event) = events.next_event()? {
+            all.push(event);
+        }
+        Ok(all)
+    }
+
+    #[test]
+    fn test_build_events_stream_then_schema() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut files = Vec::new();
+        for (name, content) in [("01.sql", "CREATE TABLE t (id INT);\n"), ("02.sql", "")] {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            files.push(path.to_str().unwrap().to_string());
+        }
+
+        let mut events = BuildEvents::start(files.clone(), None, false, ReadLimits::default());
+        let all = drain(&mut events).unwrap();
+        let kinds: Vec<&str> = all.iter().map(|e| e.kind.as_str()).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                "phase_started",
+                "file_processed",
+                "file_processed",
+                "warning",
+                "finished"
+            ]
+        );
+        assert_eq!((all[1].done, all[2].done), (1, 2));
+        assert!(all[3].message.as_deref().unwrap().ends_with("02.sql"));
+        assert!(all.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+        assert_eq!(
+            events.schema(),
+            Some(concat_files(&files, None, false).unwrap().0.as_str())
+        );
+        assert!(events.next_event().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_build_events_strict_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("empty.sql");
+        fs::write(&path, "-- nothing\n").unwrap();
+
+        let mut events = BuildEvents::start(
+            vec![path.to_str().unwrap().to_string()],
+            None,
+            true,
+            ReadLimits::default(),
+        );
+
+        assert_eq!(drain(&mut events).unwrap_err().code, ErrorCode::EmptyFile);
+        assert_eq!(events.schema(), None);
+    }
+}
@@ -0,0 +1,364 @@
+//! C ABI over the core operations
+//!
+//! Lets non-Python hosts (e.g. a Go deployment controller through cgo) call
+//! the same engine as `confiture._core` without shelling out. Declarations
+//! live in `include/confiture.h`; build the library without the Python
+//! frontend:
+//!
+//! ```text
+//! cargo build --release --no-default-features --features ffi
+//! ```
+//!
+//! ## Conventions
+//!
+//! - Input strings are NUL-terminated UTF-8
+//! - Output strings are allocated by this library and must be released with
+//!   `confiture_string_free`; out-pointers may be NULL to discard a value
+//! - Functions return 0 on success, otherwise an exit code from
+//!   `docs/reference/exit-codes.md` (4 for native errors, 5 for invalid
+//!   arguments, 1 if the result cannot be returned) and set `*error` to
+//!   `"[CODE] message"`
+//! - A panic never unwinds into the caller: it is caught at the boundary and
+//!   reported as status 1 with `*error` describing it
+//!
+//! Schema diffing lives in the Python package, not in this crate, so it has
+//! no C entry point.
+
+use crate::builder::concat_files;
+use crate::errors::CoreError;
+use crate::hasher::compute_hash;
+use crate::pgdump::{read_archive, schema_ddl};
+use std::any::Any;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+/// Invalid arguments: configuration invalid
+const STATUS_INVALID_ARGUMENT: c_int = 5;
+/// Result not representable as a C string, or an internal panic: generic
+/// failure
+const STATUS_OUTPUT: c_int = 1;
+
+enum FfiError {
+    Argument(String),
+    Core(CoreError),
+    Output(String),
+    /// A panic caught at the boundary
+    Internal(String),
+}
+
+impl From<CoreError> for FfiError {
+    fn from(err: CoreError) -> Self {
+        FfiError::Core(err)
+    }
+}
+
+/// Concatenate SQL files into one schema (C counterpart of `build_schema`)
+///
+/// `*warnings` receives the build notices separated by newlines, or NULL if
+/// there were none.
+///
+/// # Safety
+///
+/// `files` must point to `n_files` valid C strings; `base_dir` must be NULL
+/// or a valid C string; each out-pointer must be NULL or writable.
+#[no_mangle]
+pub unsafe extern "C" fn confiture_build_schema(
+    files: *const *const c_char,
+    n_files: usize,
+    base_dir: *const c_char,
+    schema: *mut *mut c_char,
+    warnings: *mut *mut c_char,
+    error: *mut *mut c_char,
+) -> c_int {
+    guarded(error, || {
+        let files = read_paths(files, n_files)?;
+        let base_dir = read_optional_str(base_dir, "base_dir")?;
+        let (output, notices) = concat_files(&files, base_dir, false)?;
+
+        let notices = if notices.is_empty() {
+            None
+        } else {
+            Some(to_c_string(notices.join("\n"))?)
+        };
+        write_out(schema, Some(to_c_string(output)?));
+        write_out(warnings, notices);
+        Ok(())
+    })
+}
+
+/// Hash SQL files (C counterpart of `hash_files`)
+///
+/// # Safety
+///
+/// As for `confiture_build_schema`.
+#[no_mangle]
+pub unsafe extern "C" fn confiture_hash_files(
+    files: *const *const c_char,
+    n_files: usize,
+    base_dir: *const c_char,
+    hash: *mut *mut c_char,
+    error: *mut *mut c_char,
+) -> c_int {
+    guarded(error, || {
+        let files = read_paths(files, n_files)?;
+        let base_dir = read_optional_str(base_dir, "base_dir")?;
+        let (digest, _) = compute_hash(&files, base_dir, false)?;
+        write_out(hash, Some(to_c_string(digest)?));
+        Ok(())
+    })
+}
+
+/// Extract schema-only DDL from a pg_dump custom-format archive (C
+/// counterpart of `extract_dump_schema`)
+///
+/// # Safety
+///
+/// `path` must be a valid C string; each out-pointer must be NULL or
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn confiture_extract_dump_schema(
+    path: *const c_char,
+    schema: *mut *mut c_char,
+    error: *mut *mut c_char,
+) -> c_int {
+    guarded(error, || {
+        let path = read_optional_str(path, "path")?
+            .ok_or_else(|| FfiError::Argument("path must not be NULL".to_string()))?;
+        let archive = read_archive(Path::new(&path))?;
+        write_out(schema, Some(to_c_string(schema_ddl(&archive.entries))?));
+        Ok(())
+    })
+}
+
+/// Release a string returned by this library
+///
+/// # Safety
+///
+/// `s` must be NULL or a pointer returned by this library that has not been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn confiture_string_free(s: *mut c_char) {
+    // Nothing to report a panic to; just keep it from unwinding into C
+    let _ = panic::catch_unwind(|| {
+        if !s.is_null() {
+            drop(CString::from_raw(s));
+        }
+    });
+}
+
+/// Run an entry point's body, turning a panic into an error status instead
+/// of unwinding across the C boundary (undefined behavior)
+unsafe fn guarded(error: *mut *mut c_char, body: impl FnOnce() -> Result<(), FfiError>) -> c_int {
+    let result = panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        Err(FfiError::Internal(format!(
+            "internal error: {}",
+            panic_message(payload.as_ref())
+        )))
+    });
+    finish(result, error)
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("panic")
+}
+
+/// Store the outcome in `*error` and map it to a status code
+unsafe fn finish(result: Result<(), FfiError>, error: *mut *mut c_char) -> c_int {
+    let (status, message) = match result {
+        Ok(()) => {
+            write_out(error, None);
+            return 0;
+        }
+        Err(FfiError::Core(err)) => (c_int::from(err.code.exit_code()), err.to_string()),
+        Err(FfiError::Argument(message)) => (STATUS_INVALID_ARGUMENT, message),
+        Err(FfiError::Output(message) | FfiError::Internal(message)) => (STATUS_OUTPUT, message),
+    };
+
+    // Messages built here never contain NUL
+    write_out(error, CString::new(message.replace('\0', "")).ok());
+    status
+}
+
+unsafe fn read_paths(files: *const *const c_char, n_files: usize) -> Result<Vec<String>, FfiError> {
+    if n_files == 0 {
+        return Ok(Vec::new());
+    }
+    if files.is_null() {
+        return Err(FfiError::Argument("files must not be NULL".to_string()));
+    }
+
+    (0..n_files)
+        .map(|i| {
+            read_optional_str(*files.add(i), "files")?
+                .ok_or_else(|| FfiError::Argument(format!("files[{}] must not be NULL", i)))
+        })
+        .collect()
+}
+
+unsafe fn read_optional_str(s: *const c_char, name: &str) -> Result<Option<String>, FfiError> {
+    if s.is_null() {
+        return Ok(None);
+    }
+
+    CStr::from_ptr(s)
+        .to_str()
+        .map(|s| Some(s.to_string()))
+        .map_err(|_| FfiError::Argument(format!("{} is not valid UTF-8", name)))
+}
+
+fn to_c_string(s: String) -> Result<CString, FfiError> {
+    CString::new(s).map_err(|e| {
+        FfiError::Output(format!(
+            "result contains a NUL byte at offset {}",
+            e.nul_position()
+        ))
+    })
+}
+
+/// Hand `value` to the caller, or drop it if the caller passed NULL
+unsafe fn write_out(out: *mut *mut c_char, value: Option<CString>) {
+    if !out.is_null() {
+        *out = value.map_or(ptr::null_mut(), CString::into_raw);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Take ownership of a string returned through an out-pointer
+    fn take(out: *mut c_char) -> Option<String> {
+        if out.is_null() {
+            return None;
+        }
+        let value = unsafe { CStr::from_ptr(out) }.to_str().unwrap().to_string();
+        unsafe { confiture_string_free(out) };
+        Some(value)
+    }
+
+    #[test]
+    fn test_build_and_hash_match_rust_api() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("01.sql");
+        fs::write(&file, "\u{feff}CREATE TABLE users (id INT);\n").unwrap();
+
+        let files = vec![file.to_str().unwrap().to_string()];
+        let c_files: Vec<CString> = files
+            .iter()
+            .map(|f| CString::new(f.as_str()).unwrap())
+            .collect();
+        let c_ptrs: Vec<*const c_char> = c_files.iter().map(|f| f.as_ptr()).collect();
+
+        let mut schema = ptr::null_mut();
+        let mut warnings = ptr::null_mut();
+        let mut error = ptr::null_mut();
+        let status = unsafe {
+            confiture_build_schema(
+                c_ptrs.as_ptr(),
+                c_ptrs.len(),
+                ptr::null(),
+                &mut schema,
+                &mut warnings,
+                &mut error,
+            )
+        };
+
+        assert_eq!(status, 0);
+        assert_eq!(take(error), None);
+        assert_eq!(
+            take(schema),
+            Some(concat_files(&files, None, false).unwrap().0)
+        );
+        assert!(take(warnings).unwrap().contains("byte order mark"));
+
+        let mut hash = ptr::null_mut();
+        let status = unsafe {
+            confiture_hash_files(
+                c_ptrs.as_ptr(),
+                c_ptrs.len(),
+                ptr::null(),
+                &mut hash,
+                ptr::null_mut(),
+            )
+        };
+
+        assert_eq!(status, 0);
+        assert_eq!(
+            take(hash),
+            compute_hash(&files, None, false).ok().map(|(hash, _)| hash)
+        );
+    }
+
+    #[test]
+    fn test_errors_carry_code_and_status() {
+        let missing = CString::new("/nonexistent/file.sql").unwrap();
+        let c_ptrs = [missing.as_ptr()];
+
+        let mut hash = ptr::null_mut();
+        let mut error = ptr::null_mut();
+        let status =
+            unsafe { confiture_hash_files(c_ptrs.as_ptr(), 1, ptr::null(), &mut hash, &mut error) };
+
+        assert_eq!(status, 4);
+        assert!(hash.is_null());
+        assert!(take(error).unwrap().starts_with("[SCHEMA_205]"));
+
+        let mut error = ptr::null_mut();
+        let status =
+            unsafe { confiture_extract_dump_schema(ptr::null(), ptr::null_mut(), &mut error) };
+
+        assert_eq!(status, STATUS_INVALID_ARGUMENT);
+        assert!(take(error).unwrap().contains("path"));
+    }
+
+    #[test]
+    fn test_build_schema_fails_on_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let present = temp_dir.path().join("01.sql");
+        fs::write(&present, "CREATE TABLE users (id INT);\n").unwrap();
+        let c_files = [
+            CString::new(present.to_str().unwrap()).unwrap(),
+            CString::new(temp_dir.path().join("02.sql").to_str().unwrap()).unwrap(),
+        ];
+        let c_ptrs: Vec<*const c_char> = c_files.iter().map(|f| f.as_ptr()).collect();
+
+        let mut schema = ptr::null_mut();
+        let mut error = ptr::null_mut();
+        let status = unsafe {
+            confiture_build_schema(
+                c_ptrs.as_ptr(),
+                c_ptrs.len(),
+                ptr::null(),
+                &mut schema,
+                ptr::null_mut(),
+                &mut error,
+            )
+        };
+
+        assert_eq!(status, 4);
+        assert!(schema.is_null());
+        let error = take(error).unwrap();
+        assert!(error.starts_with("[SCHEMA_205]") && error.contains("02.sql"));
+    }
+
+    #[test]
+    fn test_panics_become_error_status() {
+        let mut error = ptr::null_mut();
+
+        let status = unsafe { guarded(&mut error, || panic!("index out of bounds")) };
+
+        assert_eq!(status, STATUS_OUTPUT);
+        assert_eq!(
+            take(error).as_deref(),
+            Some("internal error: index out of bounds")
+        );
+    }
+}
@@ -5,124 +5,178 @@
 
 #![allow(clippy::useless_conversion)]
 
+use crate::builder::find_common_parent;
+#[cfg(feature = "python")]
+use crate::errors::warn_all;
+use crate::errors::{CoreError, ErrorCode};
+use crate::limits::{ReadLimits, Reader};
+use crate::sql::{blank_file_notice, blank_files_error, is_blank};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use rayon::prelude::*;
 use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Compute SHA256 hash of multiple files
 ///
 /// Args:
 ///     files: List of file paths to hash
+///     base_dir: Directory that hashed paths are relative to
+///         (default: common parent of `files`)
+///     strict: Fail instead of warning on empty or comment-only files
+///     max_concurrent_reads: Files read at the same time (default: one per
+///         CPU); lower it on network filesystems
+///     max_bytes_per_sec: Combined read rate limit (default: unlimited)
+///     read_retries: Extra attempts per file after a transient error such
+///         as EIO
 ///
 /// Returns:
 ///     Hex-encoded SHA256 hash
 ///
+/// Raises:
+///     NativeError: code `SCHEMA_205` if a file cannot be opened or read;
+///         code `SCHEMA_206` if a read limit is 0; code `SCHEMA_208` if
+///         `strict` and any file is empty or comment-only
+///
+/// Warns:
+///     NativeWarning: for each empty or comment-only file
+///
+/// The digest is identical to `SchemaBuilder.compute_hash()`'s Python
+/// fallback given the same `base_dir`.
+///
 /// This function is 30-60x faster than Python due to:
 /// - Parallel file reading (rayon)
 /// - Native SHA256 implementation
 /// - Efficient I/O buffering
 /// - No GIL contention
+#[cfg(feature = "python")]
 #[pyfunction]
+#[pyo3(signature = (
+    files,
+    base_dir = None,
+    strict = false,
+    max_concurrent_reads = None,
+    max_bytes_per_sec = None,
+    read_retries = 0,
+))]
 #[allow(clippy::useless_conversion)]
-pub fn hash_files(files: Vec<String>) -> PyResult<String> {
+pub fn hash_files(
+    files: Vec<String>,
+    base_dir: Option<String>,
+    strict: bool,
+    max_concurrent_reads: Option<usize>,
+    max_bytes_per_sec: Option<u64>,
+    read_retries: u32,
+) -> PyResult<String> {
+    let limits = ReadLimits {
+        max_concurrent_reads,
+        max_bytes_per_sec,
+        retries: read_retries,
+    };
+    let (hash, notices) = compute_hash_with_limits(&files, base_dir, strict, &limits)?;
+    warn_all(notices)?;
+    Ok(hash)
+}
+
+/// Hash files in order, failing with `SCHEMA_205` on the first unreadable file
+/// (pure Rust core of `hash_files`)
+///
+/// Returns the hex digest and one notice per empty or comment-only file; with
+/// `strict` those files are an error (`SCHEMA_208`) instead.
+pub fn compute_hash(
+    files: &[String],
+    base_dir: Option<String>,
+    strict: bool,
+) -> Result<(String, Vec<String>), CoreError> {
+    compute_hash_with_limits(files, base_dir, strict, &ReadLimits::default())
+}
+
+/// `compute_hash`, reading files within `limits`
+pub fn compute_hash_with_limits(
+    files: &[String],
+    base_dir: Option<String>,
+    strict: bool,
+    limits: &ReadLimits,
+) -> Result<(String, Vec<String>), CoreError> {
+    let reader = Reader::new(limits)?;
+
     // Convert to PathBuf
     let paths: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
 
-    // Find common base directory for relative paths (same as Python)
-    let base_dir = find_common_parent(&paths);
-
-    // Read all files in parallel and compute individual hashes
-    let file_hashes: Vec<(usize, Vec<u8>)> = paths
-        .par_iter()
-        .enumerate()
-        .map(|(i, path)| {
-            let mut file = File::open(path).expect("Failed to open file");
-            let mut buffer = Vec::new();
-            file.read_to_end(&mut buffer).expect("Failed to read file");
-
-            // Calculate relative path
-            let rel_path = path
-                .strip_prefix(&base_dir)
-                .unwrap_or(path)
-                .to_string_lossy();
-
-            // Hash both path AND content (matches Python behavior)
-            let mut hasher = Sha256::new();
-            // Include relative path in hash (detects file renames)
-            hasher.update(rel_path.as_bytes());
-            hasher.update(b"\x00"); // Separator
-                                    // Include file content
-            hasher.update(&buffer);
-            hasher.update(b"\x00"); // Separator
-
-            let hash = hasher.finalize().to_vec();
-
-            (i, hash)
+    // Use the caller's base directory, else find the common one
+    let base_dir = base_dir
+        .map(PathBuf::from)
+        .unwrap_or_else(|| find_common_parent(&paths));
+
+    // Read all files in parallel (collect preserves input order)
+    let contents: Vec<Vec<u8>> = reader.install(|| {
+        paths
+            .par_iter()
+            .map(|path| reader.read(path).map_err(|e| read_error(path, e)))
+            .collect::<Result<_, CoreError>>()
+    })?;
+
+    // Same check as the builder, which also ignores a leading BOM
+    let blank: Vec<&Path> = paths
+        .iter()
+        .zip(&contents)
+        .filter(|(_, content)| {
+            std::str::from_utf8(content)
+                .is_ok_and(|text| is_blank(text.strip_prefix('\u{feff}').unwrap_or(text)))
         })
+        .map(|(path, _)| path.as_path())
         .collect();
-
-    // Sort by original index to maintain order
-    let mut sorted_hashes = file_hashes;
-    sorted_hashes.sort_by_key(|(i, _)| *i);
-
-    // Combine all hashes
-    let mut final_hasher = Sha256::new();
-    for (_, hash) in sorted_hashes {
-        final_hasher.update(&hash);
+    if strict && !blank.is_empty() {
+        return Err(blank_files_error(&blank));
     }
+    let notices = blank.iter().map(|path| blank_file_notice(path)).collect();
 
-    // Return hex-encoded hash
-    Ok(format!("{:x}", final_hasher.finalize()))
+    Ok((hash_contents(&paths, &contents, &base_dir), notices))
 }
 
-/// Find common parent directory of all paths (same logic as builder)
-fn find_common_parent(paths: &[PathBuf]) -> PathBuf {
-    if paths.is_empty() {
-        return PathBuf::from(".");
-    }
-
-    if paths.len() == 1 {
-        return paths[0]
-            .parent()
-            .map(|p| p.to_path_buf())
-            .unwrap_or_else(|| PathBuf::from("."));
+/// Digest of files already read, in `paths` order (the part of
+/// `compute_hash` after reading)
+pub(crate) fn hash_contents(paths: &[PathBuf], contents: &[Vec<u8>], base_dir: &Path) -> String {
+    // Feed one running hash in file order (matches Python fallback byte-for-byte)
+    let mut hasher = Sha256::new();
+    for (path, content) in paths.iter().zip(contents) {
+        // Calculate relative path
+        let rel_path = path
+            .strip_prefix(base_dir)
+            .unwrap_or(path)
+            .to_string_lossy();
+
+        // Include relative path in hash (detects file renames)
+        hasher.update(rel_path.as_bytes());
+        hasher.update(b"\x00"); // Separator
+
+        // Include file content
+        hasher.update(content);
+        hasher.update(b"\x00"); // Separator
     }
 
-    // Get absolute paths
-    let abs_paths: Vec<PathBuf> = paths
-        .iter()
-        .map(|p| p.canonicalize().unwrap_or_else(|_| p.to_path_buf()))
-        .collect();
-
-    // Get all parent parts
-    let all_parts: Vec<Vec<&str>> = abs_paths
-        .iter()
-        .map(|p| p.iter().map(|s| s.to_str().unwrap_or("")).collect())
-        .collect();
-
-    // Find common prefix
-    let mut common_parts = Vec::new();
-    let min_len = all_parts.iter().map(|parts| parts.len()).min().unwrap_or(0);
-
-    for i in 0..min_len {
-        let part_at_level: Vec<&str> = all_parts.iter().map(|parts| parts[i]).collect();
-        let first = part_at_level[0];
-
-        if part_at_level.iter().all(|&p| p == first) {
-            common_parts.push(first);
-        } else {
-            break;
-        }
-    }
+    // Return hex-encoded hash
+    format!("{:x}", hasher.finalize())
+}
 
-    if common_parts.is_empty() {
-        return PathBuf::from(".");
-    }
+/// Read a whole file, mapping I/O failures to a structured error
+pub(crate) fn read_file(path: &Path) -> Result<Vec<u8>, CoreError> {
+    let mut buffer = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut buffer))
+        .map_err(|e| read_error(path, e))?;
+    Ok(buffer)
+}
 
-    common_parts.iter().collect()
+pub(crate) fn read_error(path: &Path, e: std::io::Error) -> CoreError {
+    CoreError::new(
+        ErrorCode::FileRead,
+        format!("Cannot read schema file {}: {}", path.display(), e),
+    )
+    .with_detail("file", path.display().to_string())
+    .with_detail("reason", e.to_string())
 }
 
 #[cfg(test)]
@@ -138,7 +192,9 @@ mod tests {
 
         fs::write(&file_path, "CREATE TABLE test (id INT);").unwrap();
 
-        let hash = hash_files(vec![file_path.to_str().unwrap().to_string()]).unwrap();
+        let hash = compute_hash(&[file_path.to_str().unwrap().to_string()], None, false)
+            .unwrap()
+            .0;
 
         // Should be valid SHA256 hex (64 characters)
         assert_eq!(hash.len(), 64);
@@ -155,11 +211,16 @@ mod tests {
         fs::write(&file1, "CREATE TABLE users (id INT);").unwrap();
         fs::write(&file2, "CREATE TABLE posts (id INT);").unwrap();
 
-        let hash = hash_files(vec![
-            file1.to_str().unwrap().to_string(),
-            file2.to_str().unwrap().to_string(),
-        ])
-        .unwrap();
+        let hash = compute_hash(
+            &[
+                file1.to_str().unwrap().to_string(),
+                file2.to_str().unwrap().to_string(),
+            ],
+            None,
+            false,
+        )
+        .unwrap()
+        .0;
 
         assert_eq!(hash.len(), 64);
     }
@@ -171,11 +232,15 @@ mod tests {
 
         // Hash with initial content
         fs::write(&file_path, "CREATE TABLE test (id INT);").unwrap();
-        let hash1 = hash_files(vec![file_path.to_str().unwrap().to_string()]).unwrap();
+        let hash1 = compute_hash(&[file_path.to_str().unwrap().to_string()], None, false)
+            .unwrap()
+            .0;
 
         // Hash with modified content
         fs::write(&file_path, "CREATE TABLE test (id BIGINT);").unwrap();
-        let hash2 = hash_files(vec![file_path.to_str().unwrap().to_string()]).unwrap();
+        let hash2 = compute_hash(&[file_path.to_str().unwrap().to_string()], None, false)
+            .unwrap()
+            .0;
 
         // Hashes should be different
         assert_ne!(hash1, hash2);
@@ -191,19 +256,101 @@ mod tests {
         fs::write(&file1, "A").unwrap();
         fs::write(&file2, "B").unwrap();
 
-        let hash1 = hash_files(vec![
-            file1.to_str().unwrap().to_string(),
-            file2.to_str().unwrap().to_string(),
-        ])
-        .unwrap();
-
-        let hash2 = hash_files(vec![
-            file2.to_str().unwrap().to_string(),
-            file1.to_str().unwrap().to_string(),
-        ])
-        .unwrap();
+        let hash1 = compute_hash(
+            &[
+                file1.to_str().unwrap().to_string(),
+                file2.to_str().unwrap().to_string(),
+            ],
+            None,
+            false,
+        )
+        .unwrap()
+        .0;
+
+        let hash2 = compute_hash(
+            &[
+                file2.to_str().unwrap().to_string(),
+                file1.to_str().unwrap().to_string(),
+            ],
+            None,
+            false,
+        )
+        .unwrap()
+        .0;
 
         // Order should affect hash
         assert_ne!(hash1, hash2);
     }
+
+    #[test]
+    fn test_hash_missing_file_returns_error_code() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing.sql");
+
+        let err = compute_hash(&[missing.to_str().unwrap().to_string()], None, false).unwrap_err();
+
+        assert_eq!(err.code, ErrorCode::FileRead);
+        assert_eq!(err.code.as_str(), "SCHEMA_205");
+        assert!(err
+            .details
+            .iter()
+            .any(|(key, value)| *key == "file" && value.ends_with("missing.sql")));
+    }
+
+    #[test]
+    fn test_hash_flags_blank_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let table = temp_dir.path().join("01_table.sql");
+        let merged = temp_dir.path().join("02_merged.sql");
+
+        fs::write(&table, "CREATE TABLE users (id INT);").unwrap();
+        fs::write(&merged, "\u{feff}-- TODO: resolve conflict\n/* lost */\n").unwrap();
+
+        let files = vec![
+            table.to_str().unwrap().to_string(),
+            merged.to_str().unwrap().to_string(),
+        ];
+
+        let (_, notices) = compute_hash(&files, None, false).unwrap();
+        assert_eq!(notices.len(), 1);
+        assert!(notices[0].ends_with("02_merged.sql"));
+
+        let err = compute_hash(&files, None, true).unwrap_err();
+        assert_eq!(err.code.as_str(), "SCHEMA_208");
+        assert!(err
+            .details
+            .iter()
+            .any(|(key, value)| *key == "files" && value.ends_with("02_merged.sql")));
+    }
+
+    #[test]
+    fn test_hash_matches_python_fallback() {
+        let temp_dir = TempDir::new().unwrap();
+        let sub_dir = temp_dir.path().join("10_tables");
+        fs::create_dir(&sub_dir).unwrap();
+
+        let file1 = sub_dir.join("01.sql");
+        let file2 = sub_dir.join("02.sql");
+
+        fs::write(&file1, "CREATE TABLE users (id INT);").unwrap();
+        fs::write(&file2, "CREATE TABLE posts (id INT);").unwrap();
+
+        let hash = compute_hash(
+            &[
+                file1.to_str().unwrap().to_string(),
+                file2.to_str().unwrap().to_string(),
+            ],
+            Some(temp_dir.path().to_str().unwrap().to_string()),
+            false,
+        )
+        .unwrap()
+        .0;
+
+        // Same byte stream as SchemaBuilder.compute_hash()'s Python fallback
+        let mut expected = Sha256::new();
+        expected.update(b"10_tables/01.sql\x00CREATE TABLE users (id INT);\x00");
+        expected.update(b"10_tables/02.sql\x00CREATE TABLE posts (id INT);\x00");
+
+        assert_eq!(hash, format!("{:x}", expected.finalize()));
+    }
 }
@@ -2,19 +2,101 @@
 //!
 //! This crate provides high-performance file operations and schema building
 //! to accelerate the Python confiture tool by 10-50x.
+//!
+//! The `_core` Python module (feature `python`, on by default) is one
+//! frontend over the pure-Rust functions re-exported below; the optional
+//! `confiture-native` binary (feature `cli`) and C ABI (feature `ffi`) are
+//! others.
 
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 mod builder;
+mod cdc;
+mod chunks;
+mod dedup;
+mod errors;
+mod events;
+#[cfg(feature = "ffi")]
+mod ffi;
 mod hasher;
+mod limits;
+mod literals;
+mod package;
+mod pgdump;
+mod sql;
+mod status;
 
-use builder::build_schema;
+#[cfg(feature = "python")]
+use builder::{build_schema, build_schema_iter};
+#[cfg(feature = "python")]
+use cdc::write_content_chunks;
+#[cfg(feature = "python")]
+use chunks::write_schema_chunks;
+#[cfg(feature = "python")]
+use dedup::find_duplicate_statements;
+#[cfg(feature = "python")]
+use errors::{NativeError, NativeWarning};
+#[cfg(feature = "python")]
+use events::build_schema_events;
+#[cfg(feature = "python")]
 use hasher::hash_files;
+#[cfg(feature = "python")]
+use literals::extract_literals;
+#[cfg(feature = "python")]
+use package::{extract_package, package_tree, verify_package};
+#[cfg(feature = "python")]
+use pgdump::{extract_dump_schema, read_dump_toc};
+#[cfg(feature = "python")]
+use status::read_status;
+
+pub use builder::{
+    concat_files, concat_files_with_progress, concat_files_with_status, BuildSchemaIter,
+};
+pub use cdc::{content_chunks, write_cdc_chunks, ChunkSizes, ContentChunk};
+pub use chunks::{verify_chunks, write_chunks, SchemaChunk};
+pub use dedup::{find_duplicates, DuplicateStatement, StatementLocation};
+pub use errors::{CoreError, ErrorCode};
+pub use events::{BuildEvents, ProgressEvent};
+pub use hasher::{compute_hash, compute_hash_with_limits};
+pub use limits::ReadLimits;
+pub use literals::{find_literals, SqlLiteral};
+pub use package::{collect_sql_files, read_package, write_package, PackageManifest};
+pub use pgdump::{read_archive, schema_ddl, DumpArchive, DumpTocEntry};
+pub use sql::{is_blank, split_statements, Statement};
+pub use status::{read_status_file, OperationStatus, StatusFile};
 
 /// Python module definition
+#[cfg(feature = "python")]
 #[pymodule]
 fn _core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(build_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(build_schema_iter, m)?)?;
+    m.add_class::<BuildSchemaIter>()?;
+    m.add_function(wrap_pyfunction!(write_schema_chunks, m)?)?;
+    m.add_class::<SchemaChunk>()?;
+    m.add_function(wrap_pyfunction!(write_content_chunks, m)?)?;
+    m.add_class::<ContentChunk>()?;
+    m.add_function(wrap_pyfunction!(build_schema_events, m)?)?;
+    m.add_class::<BuildEvents>()?;
+    m.add_class::<ProgressEvent>()?;
     m.add_function(wrap_pyfunction!(hash_files, m)?)?;
+    m.add_function(wrap_pyfunction!(read_dump_toc, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_dump_schema, m)?)?;
+    m.add_class::<DumpArchive>()?;
+    m.add_class::<DumpTocEntry>()?;
+    m.add_function(wrap_pyfunction!(find_duplicate_statements, m)?)?;
+    m.add_class::<DuplicateStatement>()?;
+    m.add_class::<StatementLocation>()?;
+    m.add_function(wrap_pyfunction!(extract_literals, m)?)?;
+    m.add_class::<SqlLiteral>()?;
+    m.add_function(wrap_pyfunction!(package_tree, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_package, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_package, m)?)?;
+    m.add_class::<PackageManifest>()?;
+    m.add_function(wrap_pyfunction!(read_status, m)?)?;
+    m.add_class::<OperationStatus>()?;
+    m.add("NativeError", m.py().get_type::<NativeError>())?;
+    m.add("NativeWarning", m.py().get_type::<NativeWarning>())?;
     Ok(())
 }
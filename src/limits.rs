@@ -0,0 +1,245 @@
+//! I/O throttling for schema trees on network filesystems
+//!
+//! Fully parallel reads can overwhelm an NFS mount until reads time out.
+//! `ReadLimits` caps how many files are read at once and how fast bytes are
+//! pulled, and retries a read that fails with a transient error (EIO on Unix,
+//! a timeout) with exponential backoff capped at a few seconds per attempt.
+//! The defaults apply no limits.
+
+use crate::errors::{CoreError, ErrorCode};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Bytes read between two pacing checks when `max_bytes_per_sec` is set
+const PACED_CHUNK: usize = 64 * 1024;
+
+/// Delay before the first retry; doubled for each further attempt
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Longest delay between two attempts, however many retries are allowed
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(5);
+
+/// `errno` for an I/O error reported by the device or mount (the same on
+/// every Unix; on Windows raw error 5 is ERROR_ACCESS_DENIED)
+#[cfg(unix)]
+const EIO: i32 = 5;
+
+/// Limits on how builds and hashes read schema files
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadLimits {
+    /// Files read at the same time (default: one per CPU)
+    pub max_concurrent_reads: Option<usize>,
+    /// Combined read rate across all files (default: unlimited)
+    pub max_bytes_per_sec: Option<u64>,
+    /// Extra attempts per file after a transient read error
+    pub retries: u32,
+}
+
+/// Reads files within a set of `ReadLimits`
+pub(crate) struct Reader {
+    pool: Option<rayon::ThreadPool>,
+    pacer: Option<Pacer>,
+    retries: u32,
+}
+
+impl Reader {
+    /// Validate `limits` and set up the reader (`SCHEMA_206` on a zero limit)
+    pub(crate) fn new(limits: &ReadLimits) -> Result<Self, CoreError> {
+        let pool = match limits.max_concurrent_reads {
+            None => None,
+            Some(0) => return Err(invalid_limit("max_concurrent_reads")),
+            Some(threads) => Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .map_err(|e| {
+                        CoreError::new(
+                            ErrorCode::InvalidOption,
+                            format!("Cannot start {} reader threads: {}", threads, e),
+                        )
+                        .with_detail("option", "max_concurrent_reads")
+                    })?,
+            ),
+        };
+        let pacer = match limits.max_bytes_per_sec {
+            None => None,
+            Some(0) => return Err(invalid_limit("max_bytes_per_sec")),
+            Some(rate) => Some(Pacer {
+                rate,
+                started: Instant::now(),
+                bytes: Mutex::new(0),
+            }),
+        };
+
+        Ok(Self {
+            pool,
+            pacer,
+            retries: limits.retries,
+        })
+    }
+
+    /// Run `op` (typically a rayon parallel iterator) with at most
+    /// `max_concurrent_reads` workers
+    pub(crate) fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// Read a whole file, pacing and retrying as configured
+    pub(crate) fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let mut attempt = 0;
+        loop {
+            match self.read_once(path) {
+                Err(e) if attempt < self.retries && is_transient(&e) => {
+                    thread::sleep(retry_backoff(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn read_once(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let mut file = File::open(path)?;
+        let mut buffer = Vec::new();
+
+        let Some(pacer) = &self.pacer else {
+            file.read_to_end(&mut buffer)?;
+            return Ok(buffer);
+        };
+
+        loop {
+            let read = (&mut file)
+                .take(PACED_CHUNK as u64)
+                .read_to_end(&mut buffer)?;
+            if read == 0 {
+                return Ok(buffer);
+            }
+            pacer.consume(read as u64);
+        }
+    }
+}
+
+/// Shared byte budget: sleeps readers that get ahead of the rate
+struct Pacer {
+    rate: u64,
+    started: Instant,
+    bytes: Mutex<u64>,
+}
+
+impl Pacer {
+    fn consume(&self, bytes: u64) {
+        let total = {
+            let mut consumed = self.bytes.lock().unwrap_or_else(|e| e.into_inner());
+            *consumed += bytes;
+            *consumed
+        };
+
+        let due = self.started + Duration::from_secs_f64(total as f64 / self.rate as f64);
+        let now = Instant::now();
+        if due > now {
+            thread::sleep(due - now);
+        }
+    }
+}
+
+/// Delay before retry number `attempt + 1`
+fn retry_backoff(attempt: u32) -> Duration {
+    RETRY_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_RETRY_BACKOFF)
+}
+
+/// Errors worth retrying on a network mount
+fn is_transient(e: &io::Error) -> bool {
+    is_eio(e)
+        || matches!(
+            e.kind(),
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted
+        )
+}
+
+#[cfg(unix)]
+fn is_eio(e: &io::Error) -> bool {
+    e.raw_os_error() == Some(EIO)
+}
+
+#[cfg(not(unix))]
+fn is_eio(_: &io::Error) -> bool {
+    false
+}
+
+fn invalid_limit(option: &'static str) -> CoreError {
+    CoreError::new(
+        ErrorCode::InvalidOption,
+        format!("{} must be greater than 0", option),
+    )
+    .with_detail("option", option)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_reader_paces_reads() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("big.sql");
+        fs::write(&path, vec![b'x'; 3 * PACED_CHUNK]).unwrap();
+
+        let reader = Reader::new(&ReadLimits {
+            max_bytes_per_sec: Some(4 * PACED_CHUNK as u64),
+            ..ReadLimits::default()
+        })
+        .unwrap();
+        let started = Instant::now();
+        let content = reader.read(&path).unwrap();
+
+        assert_eq!(content.len(), 3 * PACED_CHUNK);
+        // 3 chunks at 4 chunks/s
+        assert!(started.elapsed() >= Duration::from_millis(700));
+    }
+
+    #[test]
+    fn test_reader_rejects_zero_limits() {
+        for limits in [
+            ReadLimits {
+                max_concurrent_reads: Some(0),
+                ..ReadLimits::default()
+            },
+            ReadLimits {
+                max_bytes_per_sec: Some(0),
+                ..ReadLimits::default()
+            },
+        ] {
+            let err = Reader::new(&limits).err().unwrap();
+            assert_eq!(err.code, ErrorCode::InvalidOption);
+        }
+    }
+
+    #[test]
+    fn test_retry_backoff_is_capped() {
+        assert_eq!(retry_backoff(0), RETRY_BACKOFF);
+        assert_eq!(retry_backoff(1), 2 * RETRY_BACKOFF);
+        assert_eq!(retry_backoff(10), MAX_RETRY_BACKOFF);
+        assert_eq!(retry_backoff(u32::MAX), MAX_RETRY_BACKOFF);
+    }
+
+    #[test]
+    fn test_only_transient_errors_are_retried() {
+        #[cfg(unix)]
+        assert!(is_transient(&io::Error::from_raw_os_error(EIO)));
+        #[cfg(windows)]
+        assert!(!is_transient(&io::Error::from_raw_os_error(5)));
+        assert!(is_transient(&io::Error::from(io::ErrorKind::TimedOut)));
+        assert!(!is_transient(&io::Error::from(io::ErrorKind::NotFound)));
+    }
+}
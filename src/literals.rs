@@ -0,0 +1,316 @@
+//! String literal and identifier extraction
+//!
+//! Lexes SQL with the same rules as `crate::sql` and returns every string
+//! literal and identifier with its position, so policies such as "no
+//! hard-coded e-mail addresses" can be checked on real literals instead of
+//! regex matches that also hit comments and quoted code.
+//!
+//! A dollar-quoted body (usually a function body) is one `dollar_string`
+//! literal; run `extract_literals` on its `value` to look inside. Keywords are
+//! lexically identifiers and are reported as such.
+
+#![allow(clippy::useless_conversion)]
+
+use crate::sql::{dollar_quote_end, is_ident_byte, skip_block_comment, skip_quoted};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// A string literal or identifier found by `extract_literals`
+#[cfg_attr(feature = "python", pyclass(get_all, frozen))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlLiteral {
+    /// "string", "dollar_string", "quoted_identifier" or "identifier"
+    pub kind: String,
+    /// Unescaped contents: string text with `''` and `E'...'` escapes
+    /// resolved, a dollar-quoted body, or an identifier as PostgreSQL sees
+    /// it (quoted identifiers verbatim, others folded to lower case)
+    pub value: String,
+    /// Token as written, including quotes and any `E`/`B`/`X`/`N` prefix
+    pub text: String,
+    /// 1-based line of the first character
+    pub line: usize,
+    /// 1-based column of the first character, in characters
+    pub column: usize,
+    /// Character offsets of the token, for slicing the input string
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Find all string literals and identifiers in SQL text
+///
+/// Args:
+///     sql: SQL source
+///
+/// Returns:
+///     List of SqlLiteral in source order; comments are skipped
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn extract_literals(sql: &str) -> Vec<SqlLiteral> {
+    find_literals(sql)
+}
+
+/// Lex `sql` into string literals and identifiers (pure Rust core of
+/// `extract_literals`)
+pub fn find_literals(sql: &str) -> Vec<SqlLiteral> {
+    let bytes = sql.as_bytes();
+    let mut cursor = Cursor::default();
+    let mut literals = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let (end, kind, value) = match bytes[i] {
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                let end = bytes[i..]
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(bytes.len(), |offset| i + offset);
+                (end, None, String::new())
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                (skip_block_comment(bytes, i), None, String::new())
+            }
+            b'\'' => {
+                let end = skip_quoted(bytes, i, false);
+                (end, Some("string"), unquote(&sql[i..end], '\''))
+            }
+            b'"' => {
+                let end = skip_quoted(bytes, i, false);
+                (end, Some("quoted_identifier"), unquote(&sql[i..end], '"'))
+            }
+            b'$' => match dollar_quote_end(bytes, i) {
+                Some(end) => {
+                    let tag_end =
+                        i + 1 + bytes[i + 1..].iter().position(|&b| b == b'$').unwrap_or(0);
+                    let tag = &sql[i..=tag_end];
+                    // An unterminated body runs to the end of the input
+                    let body = &sql[tag_end + 1..end];
+                    let body = body.strip_suffix(tag).unwrap_or(body);
+                    (end, Some("dollar_string"), body.to_string())
+                }
+                // A `$1` parameter
+                None => (skip_word(bytes, i + 1), None, String::new()),
+            },
+            b if b.is_ascii_alphabetic() || b == b'_' || b >= 0x80 => {
+                let word_end = skip_word(bytes, i);
+                let prefix = &sql[i..word_end];
+                if bytes.get(word_end) == Some(&b'\'') && is_string_prefix(prefix) {
+                    let escapes = prefix.eq_ignore_ascii_case("e");
+                    let end = skip_quoted(bytes, word_end, escapes);
+                    let quoted = &sql[word_end..end];
+                    let value = if escapes {
+                        unescape(quoted)
+                    } else {
+                        unquote(quoted, '\'')
+                    };
+                    (end, Some("string"), value)
+                } else {
+                    (word_end, Some("identifier"), prefix.to_ascii_lowercase())
+                }
+            }
+            // Numbers, including forms like 1.5e10
+            b if b.is_ascii_digit() => (skip_word(bytes, i), None, String::new()),
+            _ => (i + 1, None, String::new()),
+        };
+
+        if let Some(kind) = kind {
+            let (line, column, start) = cursor.advance(sql, i);
+            let (_, _, end_char) = cursor.advance(sql, end);
+            literals.push(SqlLiteral {
+                kind: kind.to_string(),
+                value,
+                text: sql[i..end].to_string(),
+                line,
+                column,
+                start,
+                end: end_char,
+            });
+        }
+        i = end;
+    }
+
+    literals
+}
+
+/// Tracks line, column and character offset while moving forward through
+/// the input
+#[derive(Default)]
+struct Cursor {
+    byte: usize,
+    chars: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl Cursor {
+    /// Move to byte offset `to` (a char boundary at or after the current
+    /// one) and return its 1-based line, 1-based column and char offset
+    fn advance(&mut self, sql: &str, to: usize) -> (usize, usize, usize) {
+        for c in sql[self.byte..to].chars() {
+            self.chars += 1;
+            if c == '\n' {
+                self.line += 1;
+                self.line_start = self.chars;
+            }
+        }
+        self.byte = to;
+        (self.line + 1, self.chars - self.line_start + 1, self.chars)
+    }
+}
+
+/// Index just past the identifier or number characters starting at `start`
+fn skip_word(bytes: &[u8], start: usize) -> usize {
+    let mut i = start;
+    while i < bytes.len() && (is_ident_byte(bytes[i]) || bytes[i] == b'$' || bytes[i] == b'.') {
+        // `.` continues numbers (1.5) but separates qualified names
+        if bytes[i] == b'.' && !bytes[start].is_ascii_digit() {
+            break;
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Prefixes that make `X'...'` a string: escape, bit, hex and national
+fn is_string_prefix(word: &str) -> bool {
+    ["e", "b", "x", "n"]
+        .iter()
+        .any(|prefix| word.eq_ignore_ascii_case(prefix))
+}
+
+/// Contents of a quoted token with doubled quotes collapsed
+fn unquote(token: &str, quote: char) -> String {
+    let inner = token.strip_prefix(quote).unwrap_or(token);
+    let inner = inner.strip_suffix(quote).unwrap_or(inner);
+    let doubled: String = [quote, quote].iter().collect();
+    inner.replace(&doubled, &quote.to_string())
+}
+
+/// Contents of an `E'...'` string with backslash escapes resolved
+fn unescape(token: &str) -> String {
+    let inner = token.strip_prefix('\'').unwrap_or(token);
+    let inner = inner.strip_suffix('\'').unwrap_or(inner);
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                value.push('\'');
+            }
+            '\\' => match chars.next() {
+                Some('b') => value.push('\u{8}'),
+                Some('f') => value.push('\u{c}'),
+                Some('n') => value.push('\n'),
+                Some('r') => value.push('\r'),
+                Some('t') => value.push('\t'),
+                Some(kind @ ('x' | 'u' | 'U' | '0'..='7')) => {
+                    let (radix, max_digits) = match kind {
+                        'x' => (16, 2),
+                        'u' => (16, 4),
+                        'U' => (16, 8),
+                        _ => (8, 3),
+                    };
+                    let mut digits = String::new();
+                    if radix == 8 {
+                        digits.push(kind);
+                    }
+                    while digits.len() < max_digits
+                        && chars.peek().is_some_and(|d| d.is_digit(radix))
+                    {
+                        digits.extend(chars.next());
+                    }
+                    match u32::from_str_radix(&digits, radix)
+                        .ok()
+                        .and_then(char::from_u32)
+                    {
+                        Some(decoded) => value.push(decoded),
+                        // `\x` without digits is just `x`, as in PostgreSQL
+                        None => {
+                            value.push(kind);
+                            value.push_str(&digits);
+                        }
+                    }
+                }
+                Some(other) => value.push(other),
+                None => value.push('\\'),
+            },
+            _ => value.push(c),
+        }
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(sql: &str) -> Vec<(String, String)> {
+        find_literals(sql)
+            .into_iter()
+            .map(|literal| (literal.kind, literal.value))
+            .collect()
+    }
+
+    fn pair(kind: &str, value: &str) -> (String, String) {
+        (kind.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_find_literals_kinds_and_values() {
+        let sql = "INSERT INTO crm.\"Contact\" VALUES ('o''brien@example.com', E'a\\tb\\'c', $1, 4.5) -- 'not@this.one'\n/* \"nor\" */";
+
+        assert_eq!(
+            summary(sql),
+            vec![
+                pair("identifier", "insert"),
+                pair("identifier", "into"),
+                pair("identifier", "crm"),
+                pair("quoted_identifier", "Contact"),
+                pair("identifier", "values"),
+                pair("string", "o'brien@example.com"),
+                pair("string", "a\tb'c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_literals_dollar_bodies_are_one_literal() {
+        let sql = "CREATE FUNCTION f() RETURNS text AS $fn$\n  SELECT 'inner@example.com';\n$fn$ LANGUAGE sql";
+        let literals = find_literals(sql);
+        let body = literals
+            .iter()
+            .find(|literal| literal.kind == "dollar_string")
+            .unwrap();
+
+        assert_eq!(body.value, "\n  SELECT 'inner@example.com';\n");
+        assert!(!literals.iter().any(|literal| literal.kind == "string"));
+        assert_eq!(
+            summary(&body.value),
+            vec![
+                pair("identifier", "select"),
+                pair("string", "inner@example.com")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_literals_positions_are_characters() {
+        let sql = "-- café\nSELECT 'é', \"x\"";
+        let literals = find_literals(sql);
+        let chars: Vec<char> = sql.chars().collect();
+
+        for literal in &literals {
+            let sliced: String = chars[literal.start..literal.end].iter().collect();
+            assert_eq!(sliced, literal.text);
+        }
+        assert_eq!((literals[1].line, literals[1].column), (2, 8));
+        assert_eq!((literals[2].line, literals[2].column), (2, 13));
+    }
+
+    #[test]
+    fn test_unescape_numeric_escapes() {
+        assert_eq!(unescape("'\\x41\\101\\u00e9\\q\\\\'"), "AAéq\\");
+    }
+}
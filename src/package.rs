@@ -0,0 +1,583 @@
+//! Schema-tree packaging into a single verifiable artifact
+//!
+//! `package_tree` builds a schema directory and writes one uncompressed tar
+//! archive (readable with plain `tar`) holding:
+//! - `schema.sql`: the built schema, as `build_schema` produces it
+//! - `MANIFEST`: format, version, tree hash, schema digest and the SHA256 of
+//!   every source file in build order
+//! - `FINGERPRINT`: SHA256 of `MANIFEST`, which pins everything else
+//!
+//! Entries carry fixed metadata (mtime 0, mode 0644), so packaging the same
+//! tree twice yields byte-identical archives. `verify_package` recomputes the
+//! digests before apply and rejects archives holding any other, or repeated,
+//! entry (`tar -x` would extract the last copy). This guards integrity, not
+//! authenticity: sign the archive with the release tooling (e.g. cosign or
+//! gpg) if that is needed.
+
+#![allow(clippy::useless_conversion)]
+
+use crate::builder::{assemble_schema, decode_sql_file};
+#[cfg(feature = "python")]
+use crate::errors::warn_all;
+use crate::errors::{CoreError, ErrorCode};
+use crate::hasher::{hash_contents, read_error, read_file};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const FORMAT: &str = "confiture-package/1";
+const SCHEMA_ENTRY: &str = "schema.sql";
+const MANIFEST_ENTRY: &str = "MANIFEST";
+const FINGERPRINT_ENTRY: &str = "FINGERPRINT";
+const BLOCK: usize = 512;
+
+/// Contents of a verified package's manifest
+#[cfg_attr(feature = "python", pyclass(get_all, frozen))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageManifest {
+    /// Release version given to `package_tree`, if any
+    pub version: Option<String>,
+    /// SHA256 of the manifest; identifies the package
+    pub fingerprint: String,
+    /// `hash_files` digest of the source tree (relative to the schema dir)
+    pub tree_hash: String,
+    /// SHA256 of `schema.sql`
+    pub schema_sha256: String,
+    /// `(relative path, SHA256)` of each source file in build order
+    pub files: Vec<(String, String)>,
+}
+
+/// Build a schema directory into a package archive
+///
+/// Args:
+///     schema_dir: Directory searched recursively for `*.sql` files, built in
+///         path order
+///     out_path: Archive file to write (overwritten)
+///     version: Release version recorded in the manifest
+///
+/// Returns:
+///     The package fingerprint
+///
+/// Raises:
+///     NativeError: code `SCHEMA_205` if a file cannot be read,
+///         `SCHEMA_208` if `schema_dir` holds no `.sql` files, or
+///         `SCHEMA_209` if the archive cannot be written
+///
+/// Warns:
+///     NativeWarning: as for `build_schema`
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (schema_dir, out_path, version = None))]
+pub fn package_tree(
+    schema_dir: String,
+    out_path: String,
+    version: Option<String>,
+) -> PyResult<String> {
+    let (manifest, notices) = write_package(
+        Path::new(&schema_dir),
+        Path::new(&out_path),
+        version.as_deref(),
+    )?;
+    warn_all(notices)?;
+    Ok(manifest.fingerprint)
+}
+
+/// Check a package archive's integrity
+///
+/// Args:
+///     path: Archive written by `package_tree`
+///
+/// Returns:
+///     PackageManifest describing the verified package
+///
+/// Raises:
+///     NativeError: code `SCHEMA_205` if the file cannot be read, or
+///         `SCHEMA_210` if it is malformed or any digest does not match
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn verify_package(path: String) -> PyResult<PackageManifest> {
+    Ok(read_package(Path::new(&path))?.0)
+}
+
+/// Verify a package archive and return its schema
+///
+/// Args:
+///     path: Archive written by `package_tree`
+///
+/// Returns:
+///     The verified `schema.sql`, ready to apply
+///
+/// Raises:
+///     NativeError: as for `verify_package`
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn extract_package(path: String) -> PyResult<String> {
+    Ok(read_package(Path::new(&path))?.1)
+}
+
+/// All `*.sql` files under `dir` (symlinked files included), sorted by path
+/// components like Python's `sorted()` over `Path`s
+///
+/// A directory that cannot be listed or a `.sql` symlink that does not lead
+/// to a file is a `SCHEMA_205` error rather than a silently missing file.
+pub fn collect_sql_files(dir: &Path) -> Result<Vec<String>, CoreError> {
+    let mut files = Vec::new();
+    for entry in WalkDir::new(dir) {
+        let entry = entry.map_err(|e| {
+            let path = e.path().unwrap_or(dir).to_path_buf();
+            read_error(&path, e.into())
+        })?;
+        if entry.file_type().is_dir() || entry.path().extension().is_none_or(|ext| ext != "sql") {
+            continue;
+        }
+        // Follows symlinks, so a dangling link fails here
+        let metadata = fs::metadata(entry.path()).map_err(|e| read_error(entry.path(), e))?;
+        if !metadata.is_file() {
+            return Err(read_error(
+                entry.path(),
+                std::io::Error::other("not a regular file"),
+            ));
+        }
+        files.push(entry.into_path());
+    }
+
+    // `Path` orders component by component, so "a/b.sql" < "a-b.sql"
+    files.sort();
+    Ok(files
+        .into_iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
+
+/// Build and write a package (pure Rust core of `package_tree`)
+///
+/// Returns the manifest and the build notices.
+pub fn write_package(
+    schema_dir: &Path,
+    out_path: &Path,
+    version: Option<&str>,
+) -> Result<(PackageManifest, Vec<String>), CoreError> {
+    let paths: Vec<PathBuf> = collect_sql_files(schema_dir)?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+    if paths.is_empty() {
+        return Err(CoreError::new(
+            ErrorCode::EmptyFile,
+            format!("No .sql files to package under {}", schema_dir.display()),
+        )
+        .with_detail("file", schema_dir.display().to_string()));
+    }
+
+    // Read each file once; the schema, tree hash and per-file digests all
+    // come from the same bytes, so they cannot disagree
+    let contents = paths
+        .par_iter()
+        .map(|path| read_file(path))
+        .collect::<Result<Vec<_>, CoreError>>()?;
+
+    let tree_hash = hash_contents(&paths, &contents, schema_dir);
+    let file_hashes = paths
+        .iter()
+        .zip(&contents)
+        .map(|(path, content)| {
+            let rel_path = path.strip_prefix(schema_dir).unwrap_or(path);
+            (rel_path.to_string_lossy().into_owned(), sha256_hex(content))
+        })
+        .collect();
+
+    let loaded = paths
+        .iter()
+        .zip(contents)
+        .map(|(path, content)| decode_sql_file(path, content))
+        .collect::<Result<Vec<_>, CoreError>>()?;
+    let (schema, notices) = assemble_schema(&paths, loaded, schema_dir, false)?;
+
+    let mut manifest = PackageManifest {
+        version: version.map(str::to_string),
+        fingerprint: String::new(),
+        tree_hash,
+        schema_sha256: sha256_hex(schema.as_bytes()),
+        files: file_hashes,
+    };
+    let manifest_text = render_manifest(&manifest);
+    manifest.fingerprint = sha256_hex(manifest_text.as_bytes());
+
+    let mut archive = Vec::new();
+    append_entry(&mut archive, SCHEMA_ENTRY, schema.as_bytes());
+    append_entry(&mut archive, MANIFEST_ENTRY, manifest_text.as_bytes());
+    append_entry(
+        &mut archive,
+        FINGERPRINT_ENTRY,
+        format!("{}\n", manifest.fingerprint).as_bytes(),
+    );
+    archive.resize(archive.len() + 2 * BLOCK, 0);
+
+    write_atomically(out_path, &archive).map_err(|e| {
+        CoreError::new(
+            ErrorCode::FileWrite,
+            format!("Cannot write schema package {}: {}", out_path.display(), e),
+        )
+        .with_detail("file", out_path.display().to_string())
+        .with_detail("reason", e.to_string())
+    })?;
+
+    Ok((manifest, notices))
+}
+
+/// Write through `<path>.tmp`, then rename, so a failed or interrupted write
+/// never leaves a truncated archive at `path`
+fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// Read and verify a package (pure Rust core of `verify_package` and
+/// `extract_package`)
+///
+/// Returns the manifest and the verified schema.
+pub fn read_package(path: &Path) -> Result<(PackageManifest, String), CoreError> {
+    let bytes = read_file(path)?;
+
+    verify_archive(&bytes).map_err(|reason| {
+        CoreError::new(
+            ErrorCode::InvalidPackage,
+            format!(
+                "Schema package {} failed verification: {}",
+                path.display(),
+                reason
+            ),
+        )
+        .with_detail("file", path.display().to_string())
+        .with_detail("reason", reason)
+    })
+}
+
+fn verify_archive(bytes: &[u8]) -> Result<(PackageManifest, String), String> {
+    let entries = read_entries(bytes)?;
+    for (i, (name, _)) in entries.iter().enumerate() {
+        if ![SCHEMA_ENTRY, MANIFEST_ENTRY, FINGERPRINT_ENTRY].contains(&name.as_str()) {
+            return Err(format!("unexpected entry '{}'", name));
+        }
+        if entries[..i].iter().any(|(earlier, _)| earlier == name) {
+            return Err(format!("duplicate entry '{}'", name));
+        }
+    }
+    let entry = |name: &str| {
+        entries
+            .iter()
+            .find(|(entry_name, _)| entry_name == name)
+            .map(|(_, data)| *data)
+            .ok_or_else(|| format!("missing {}", name))
+    };
+
+    let manifest_bytes = entry(MANIFEST_ENTRY)?;
+    let fingerprint = String::from_utf8_lossy(entry(FINGERPRINT_ENTRY)?)
+        .trim()
+        .to_string();
+    if sha256_hex(manifest_bytes) != fingerprint {
+        return Err("MANIFEST does not match FINGERPRINT".to_string());
+    }
+
+    let manifest_text = std::str::from_utf8(manifest_bytes)
+        .map_err(|_| "MANIFEST is not valid UTF-8".to_string())?;
+    let mut manifest = parse_manifest(manifest_text)?;
+    manifest.fingerprint = fingerprint;
+
+    let schema_bytes = entry(SCHEMA_ENTRY)?;
+    if sha256_hex(schema_bytes) != manifest.schema_sha256 {
+        return Err("schema.sql does not match MANIFEST".to_string());
+    }
+    let schema = String::from_utf8(schema_bytes.to_vec())
+        .map_err(|_| "schema.sql is not valid UTF-8".to_string())?;
+
+    Ok((manifest, schema))
+}
+
+fn render_manifest(manifest: &PackageManifest) -> String {
+    let mut text = format!(
+        "format: {}\nversion: {}\ntree_hash: {}\nschema_sha256: {}\nfiles:\n",
+        FORMAT,
+        manifest.version.as_deref().unwrap_or(""),
+        manifest.tree_hash,
+        manifest.schema_sha256,
+    );
+    for (path, digest) in &manifest.files {
+        text.push_str(&format!("{}  {}\n", digest, path));
+    }
+    text
+}
+
+fn parse_manifest(text: &str) -> Result<PackageManifest, String> {
+    let mut lines = text.lines();
+    let mut field = |name: &str| {
+        lines
+            .next()
+            .and_then(|line| line.strip_prefix(name))
+            .and_then(|rest| rest.strip_prefix(": ").or(rest.strip_prefix(":")))
+            .map(str::to_string)
+            .ok_or_else(|| format!("MANIFEST is missing '{}'", name))
+    };
+
+    let format = field("format")?;
+    if format != FORMAT {
+        return Err(format!("unsupported package format '{}'", format));
+    }
+    let version = field("version")?;
+    let tree_hash = field("tree_hash")?;
+    let schema_sha256 = field("schema_sha256")?;
+    field("files")?;
+
+    let files = lines
+        .map(|line| {
+            line.split_once("  ")
+                .map(|(digest, path)| (path.to_string(), digest.to_string()))
+                .ok_or_else(|| format!("malformed MANIFEST line '{}'", line))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(PackageManifest {
+        version: Some(version).filter(|v| !v.is_empty()),
+        fingerprint: String::new(),
+        tree_hash,
+        schema_sha256,
+        files,
+    })
+}
+
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Append a regular-file ustar entry with fixed metadata
+fn append_entry(archive: &mut Vec<u8>, name: &str, data: &[u8]) {
+    let mut header = [0u8; BLOCK];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[108..116].copy_from_slice(b"0000000\0");
+    header[116..124].copy_from_slice(b"0000000\0");
+    header[124..136].copy_from_slice(format!("{:011o}\0", data.len()).as_bytes());
+    header[136..148].copy_from_slice(b"00000000000\0");
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // Checksum is computed with its own field set to spaces
+    header[148..156].copy_from_slice(b"        ");
+    let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+
+    archive.extend_from_slice(&header);
+    archive.extend_from_slice(data);
+    archive.resize(archive.len().next_multiple_of(BLOCK), 0);
+}
+
+/// Entries of a ustar archive as `(name, data)`; anything but a regular file
+/// (directory, link, extended header) is rejected
+fn read_entries(bytes: &[u8]) -> Result<Vec<(String, &[u8])>, String> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+
+    while offset + BLOCK <= bytes.len() {
+        let header = &bytes[offset..offset + BLOCK];
+        if header.iter().all(|&b| b == 0) {
+            return Ok(entries);
+        }
+
+        let mut expected: u32 = header.iter().map(|&b| u32::from(b)).sum();
+        expected -= header[148..156].iter().map(|&b| u32::from(b)).sum::<u32>();
+        expected += 8 * u32::from(b' ');
+        if parse_octal(&header[148..156]) != Some(expected as usize) {
+            return Err(format!("corrupt tar header at offset {}", offset));
+        }
+
+        let name_len = header[..100].iter().position(|&b| b == 0).unwrap_or(100);
+        let name = String::from_utf8_lossy(&header[..name_len]).into_owned();
+        let size = parse_octal(&header[124..136])
+            .ok_or_else(|| format!("corrupt size for entry '{}'", name))?;
+        let data_start = offset + BLOCK;
+        let data = bytes
+            .get(data_start..data_start + size)
+            .ok_or_else(|| format!("entry '{}' is truncated", name))?;
+
+        if !matches!(header[156], b'0' | 0) {
+            return Err(format!("entry '{}' is not a regular file", name));
+        }
+        entries.push((name, data));
+        offset = data_start + size.next_multiple_of(BLOCK);
+    }
+
+    Err("archive is truncated or not a tar file".to_string())
+}
+
+fn parse_octal(field: &[u8]) -> Option<usize> {
+    let digits: &[u8] = field
+        .split(|&b| b == 0 || b == b' ')
+        .find(|part| !part.is_empty())?;
+    usize::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::concat_files;
+    use crate::hasher::compute_hash;
+    use tempfile::TempDir;
+
+    fn schema_tree() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let tables = temp_dir.path().join("10_tables");
+        fs::create_dir(&tables).unwrap();
+        fs::write(tables.join("users.sql"), "CREATE TABLE users (id INT);\n").unwrap();
+        fs::write(
+            temp_dir.path().join("20_views.sql"),
+            "CREATE VIEW v AS SELECT 1;\n",
+        )
+        .unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_package_round_trip() {
+        let tree = schema_tree();
+        let out_dir = TempDir::new().unwrap();
+        let out_path = out_dir.path().join("schema.pkg");
+
+        let (written, _) = write_package(tree.path(), &out_path, Some("1.2.0")).unwrap();
+        let (verified, schema) = read_package(&out_path).unwrap();
+
+        assert_eq!(verified, written);
+        assert_eq!(sha256_hex(schema.as_bytes()), verified.schema_sha256);
+        assert_eq!(verified.version.as_deref(), Some("1.2.0"));
+        assert_eq!(
+            verified
+                .files
+                .iter()
+                .map(|(path, _)| path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["10_tables/users.sql", "20_views.sql"]
+        );
+        let files = collect_sql_files(tree.path()).unwrap();
+        let base_dir = Some(tree.path().to_string_lossy().into_owned());
+        assert_eq!(
+            verified.tree_hash,
+            compute_hash(&files, base_dir.clone(), false).unwrap().0
+        );
+        assert_eq!(schema, concat_files(&files, base_dir, false).unwrap().0);
+        assert!(!out_dir.path().join("schema.pkg.tmp").exists());
+    }
+
+    #[test]
+    fn test_package_rejects_empty_tree() {
+        let tree = TempDir::new().unwrap();
+        let out_dir = TempDir::new().unwrap();
+        let out_path = out_dir.path().join("schema.pkg");
+
+        let err = write_package(tree.path(), &out_path, None).unwrap_err();
+
+        assert_eq!(err.code, ErrorCode::EmptyFile);
+        assert!(!out_path.exists());
+    }
+
+    #[test]
+    fn test_package_is_reproducible() {
+        let tree = schema_tree();
+        let out_dir = TempDir::new().unwrap();
+        let first = out_dir.path().join("a.pkg");
+        let second = out_dir.path().join("b.pkg");
+
+        write_package(tree.path(), &first, None).unwrap();
+        write_package(tree.path(), &second, None).unwrap();
+
+        assert_eq!(fs::read(first).unwrap(), fs::read(second).unwrap());
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let tree = schema_tree();
+        let out_dir = TempDir::new().unwrap();
+        let out_path = out_dir.path().join("schema.pkg");
+        write_package(tree.path(), &out_path, None).unwrap();
+
+        // Same-length edit inside schema.sql keeps the tar structure intact
+        let mut bytes = fs::read(&out_path).unwrap();
+        let at = bytes.windows(5).position(|w| w == b"users").unwrap();
+        bytes[at..at + 5].copy_from_slice(b"USERS");
+        fs::write(&out_path, &bytes).unwrap();
+
+        let err = read_package(&out_path).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidPackage);
+        assert!(err.message.contains("schema.sql does not match"));
+
+        fs::write(&out_path, b"not a package").unwrap();
+        assert_eq!(
+            read_package(&out_path).unwrap_err().code,
+            ErrorCode::InvalidPackage
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_duplicate_and_unknown_entries() {
+        let tree = schema_tree();
+        let out_dir = TempDir::new().unwrap();
+        let out_path = out_dir.path().join("schema.pkg");
+        write_package(tree.path(), &out_path, None).unwrap();
+        let bytes = fs::read(&out_path).unwrap();
+        // Drop the end-of-archive blocks, then append one more entry
+        let entries = &bytes[..bytes.len() - 2 * BLOCK];
+
+        for (name, reason) in [
+            (SCHEMA_ENTRY, "duplicate entry 'schema.sql'"),
+            ("extra.sql", "unexpected entry 'extra.sql'"),
+        ] {
+            let mut doctored = entries.to_vec();
+            append_entry(&mut doctored, name, b"DROP TABLE users;\n");
+            doctored.resize(doctored.len() + 2 * BLOCK, 0);
+            fs::write(&out_path, &doctored).unwrap();
+
+            let err = read_package(&out_path).unwrap_err();
+            assert_eq!(err.code, ErrorCode::InvalidPackage);
+            assert!(err.message.contains(reason), "{}", err.message);
+        }
+    }
+
+    #[test]
+    fn test_collect_sql_files_sorts_by_component() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("a")).unwrap();
+        fs::write(temp_dir.path().join("a").join("b.sql"), "").unwrap();
+        fs::write(temp_dir.path().join("a-b.sql"), "").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "").unwrap();
+
+        let files = collect_sql_files(temp_dir.path()).unwrap();
+
+        let names: Vec<&str> = files
+            .iter()
+            .map(|f| f.strip_prefix(temp_dir.path().to_str().unwrap()).unwrap())
+            .collect();
+        assert_eq!(names, vec!["/a/b.sql", "/a-b.sql"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collect_sql_files_follows_and_checks_symlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("real.sql"), "").unwrap();
+        std::os::unix::fs::symlink("real.sql", temp_dir.path().join("linked.sql")).unwrap();
+
+        assert_eq!(collect_sql_files(temp_dir.path()).unwrap().len(), 2);
+
+        std::os::unix::fs::symlink("missing.sql", temp_dir.path().join("dangling.sql")).unwrap();
+        let err = collect_sql_files(temp_dir.path()).unwrap_err();
+        assert_eq!(err.code, ErrorCode::FileRead);
+        assert!(err.message.contains("dangling.sql"));
+    }
+}
@@ -0,0 +1,560 @@
+//! pg_dump custom-format archive reader
+//!
+//! Reads the table of contents (TOC) of `pg_dump -Fc` archives directly, so
+//! schema DDL, object lists and dependencies can be extracted from a `.dump`
+//! backup without restoring it or invoking `pg_restore`.
+//!
+//! The TOC holds every object's definition as plain text; only table data is
+//! compressed, and it is never read here: the file is read through a buffer
+//! and reading stops at the end of the TOC, so a multi-gigabyte dump costs
+//! about as much as its TOC. Supported archive versions are 1.12
+//! (PostgreSQL 9.6) through 1.16 (PostgreSQL 17).
+
+#![allow(clippy::useless_conversion)]
+
+use crate::errors::{CoreError, ErrorCode};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+const MAGIC: &[u8] = b"PGDMP";
+const FORMAT_CUSTOM: u8 = 1;
+const MIN_VERSION: (u8, u8) = (1, 12);
+const MAX_VERSION: (u8, u8) = (1, 16);
+
+/// Session settings and database-level entries `pg_restore --schema-only`
+/// leaves out unless `--create` is given
+const NON_SCHEMA_DESCS: &[&str] = &[
+    "ENCODING",
+    "STDSTRINGS",
+    "SEARCHPATH",
+    "DATABASE",
+    "DATABASE PROPERTIES",
+];
+
+/// One object in a pg_dump archive's table of contents
+#[cfg_attr(feature = "python", pyclass(get_all, frozen))]
+#[derive(Debug, Clone)]
+pub struct DumpTocEntry {
+    /// Archive-local id referenced by `dependencies`
+    pub dump_id: i64,
+    /// Object kind as printed by `pg_restore -l` (e.g. "TABLE", "INDEX")
+    pub desc: String,
+    /// Object name
+    pub tag: String,
+    /// Schema, empty for global objects
+    pub namespace: String,
+    pub owner: String,
+    /// "PRE_DATA", "DATA", "POST_DATA" or "NONE"
+    pub section: String,
+    /// CREATE statement (empty for data entries)
+    pub defn: String,
+    /// `dump_id`s this entry depends on
+    pub dependencies: Vec<i64>,
+}
+
+/// Header and table of contents of a pg_dump custom-format archive
+#[cfg_attr(feature = "python", pyclass(get_all, frozen))]
+#[derive(Debug, Clone)]
+pub struct DumpArchive {
+    /// Archive format version, e.g. "1.15"
+    pub archive_version: String,
+    /// Version of the server that was dumped
+    pub server_version: String,
+    /// Version of pg_dump that wrote the archive
+    pub pg_dump_version: String,
+    pub database: String,
+    pub entries: Vec<DumpTocEntry>,
+}
+
+/// Read the table of contents of a pg_dump custom-format archive
+///
+/// Args:
+///     path: Path to a `.dump` file written by `pg_dump -Fc`
+///
+/// Returns:
+///     DumpArchive with header fields and TOC entries in archive order
+///     (pg_dump's dependency-sorted order)
+///
+/// Raises:
+///     NativeError: code `SCHEMA_205` if the file cannot be read, or
+///         `SCHEMA_207` if it is not a supported custom-format archive
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn read_dump_toc(path: String) -> PyResult<DumpArchive> {
+    Ok(read_archive(Path::new(&path))?)
+}
+
+/// Extract schema-only DDL from a pg_dump custom-format archive
+///
+/// Args:
+///     path: Path to a `.dump` file written by `pg_dump -Fc`
+///
+/// Returns:
+///     Definitions of every schema object in archive order, like
+///     `pg_restore --schema-only` without its session preamble
+///
+/// Raises:
+///     NativeError: code `SCHEMA_205` or `SCHEMA_207` as for `read_dump_toc`
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn extract_dump_schema(path: String) -> PyResult<String> {
+    let archive = read_archive(Path::new(&path))?;
+    Ok(schema_ddl(&archive.entries))
+}
+
+/// Concatenate the DDL of all schema-object entries
+pub fn schema_ddl(entries: &[DumpTocEntry]) -> String {
+    let mut output = String::new();
+    for entry in entries {
+        if entry.section == "DATA"
+            || entry.defn.trim().is_empty()
+            || NON_SCHEMA_DESCS.contains(&entry.desc.as_str())
+        {
+            continue;
+        }
+        output.push_str(entry.defn.trim_end());
+        output.push_str("\n\n");
+    }
+    output
+}
+
+/// Read and parse an archive's header and TOC (pure Rust core of
+/// `read_dump_toc`)
+pub fn read_archive(path: &Path) -> Result<DumpArchive, CoreError> {
+    let read_error = |e: io::Error| {
+        CoreError::new(
+            ErrorCode::FileRead,
+            format!("Cannot read pg_dump archive {}: {}", path.display(), e),
+        )
+        .with_detail("file", path.display().to_string())
+        .with_detail("reason", e.to_string())
+    };
+
+    let file = File::open(path).map_err(read_error)?;
+    let mut reader = ArchiveReader::new(BufReader::new(file));
+
+    parse_archive(&mut reader).map_err(|reason| match reader.io_error.take() {
+        Some(e) => read_error(e),
+        None => CoreError::new(
+            ErrorCode::InvalidArchive,
+            format!("Invalid pg_dump archive {}: {}", path.display(), reason),
+        )
+        .with_detail("file", path.display().to_string())
+        .with_detail("reason", reason),
+    })
+}
+
+/// Parse an archive up to the end of its TOC, returning a human-readable
+/// reason on failure
+fn parse_archive<R: Read>(reader: &mut ArchiveReader<R>) -> Result<DumpArchive, String> {
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err("missing PGDMP signature (not a custom-format archive)".to_string());
+    }
+
+    let vmaj = reader.byte()?;
+    let vmin = reader.byte()?;
+    let _vrev = reader.byte()?;
+    let version = (vmaj, vmin);
+    if !(MIN_VERSION..=MAX_VERSION).contains(&version) {
+        return Err(format!("unsupported archive version {}.{}", vmaj, vmin));
+    }
+
+    reader.int_size = usize::from(reader.byte()?);
+    reader.off_size = usize::from(reader.byte()?);
+    if !(1..=8).contains(&reader.int_size) || !(1..=8).contains(&reader.off_size) {
+        return Err("corrupt header (invalid integer size)".to_string());
+    }
+
+    let format = reader.byte()?;
+    if format != FORMAT_CUSTOM {
+        return Err(format!("archive format {} is not custom (-Fc)", format));
+    }
+
+    // Compression: algorithm byte since 1.15, level int before
+    if version >= (1, 15) {
+        reader.byte()?;
+    } else {
+        reader.int()?;
+    }
+
+    // Creation timestamp: sec, min, hour, mday, mon, year, isdst
+    for _ in 0..7 {
+        reader.int()?;
+    }
+
+    let database = reader.string()?;
+    let server_version = reader.string()?;
+    let pg_dump_version = reader.string()?;
+
+    let toc_count = reader.int()?;
+    if toc_count < 0 {
+        return Err("corrupt header (negative TOC count)".to_string());
+    }
+
+    let mut entries = Vec::new();
+    for _ in 0..toc_count {
+        entries.push(reader.toc_entry(version)?);
+    }
+
+    Ok(DumpArchive {
+        archive_version: format!("{}.{}", vmaj, vmin),
+        server_version,
+        pg_dump_version,
+        database,
+        entries,
+    })
+}
+
+/// Sequential reader over an archive using pg_dump's integer encoding
+struct ArchiveReader<R> {
+    reader: R,
+    /// Bytes consumed so far, for error messages
+    pos: u64,
+    int_size: usize,
+    off_size: usize,
+    /// I/O failure behind the last error, as opposed to malformed content
+    io_error: Option<io::Error>,
+}
+
+impl<R: Read> ArchiveReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            pos: 0,
+            int_size: 4,
+            off_size: 8,
+            io_error: None,
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<Vec<u8>, String> {
+        // Grows with the data read, so a corrupt length cannot force a huge
+        // allocation up front
+        let mut buffer = Vec::new();
+        let read = (&mut self.reader)
+            .take(len as u64)
+            .read_to_end(&mut buffer)
+            .map_err(|e| {
+                let reason = format!("read failed at byte {}: {}", self.pos, e);
+                self.io_error = Some(e);
+                reason
+            })?;
+        if read < len {
+            return Err(format!("unexpected end of file at byte {}", self.pos));
+        }
+        self.pos += read as u64;
+        Ok(buffer)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Sign byte followed by `int_size` little-endian magnitude bytes
+    fn int(&mut self) -> Result<i64, String> {
+        let start = self.pos;
+        let negative = self.byte()? != 0;
+        let magnitude = self
+            .take(self.int_size)?
+            .iter()
+            .rev()
+            .fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+        i64::try_from(magnitude)
+            .ok()
+            .and_then(|value| {
+                if negative {
+                    value.checked_neg()
+                } else {
+                    Some(value)
+                }
+            })
+            .ok_or_else(|| format!("integer out of range at byte {}", start))
+    }
+
+    /// Length-prefixed string; length -1 encodes NULL (read as empty)
+    fn string(&mut self) -> Result<String, String> {
+        Ok(self.optional_string()?.unwrap_or_default())
+    }
+
+    fn optional_string(&mut self) -> Result<Option<String>, String> {
+        let len = self.int()?;
+        if len < 0 {
+            return Ok(None);
+        }
+        let raw = self.take(len as usize)?;
+        Ok(Some(String::from_utf8_lossy(&raw).into_owned()))
+    }
+
+    fn toc_entry(&mut self, version: (u8, u8)) -> Result<DumpTocEntry, String> {
+        let dump_id = self.int()?;
+        let _had_dumper = self.int()?;
+        let _table_oid = self.string()?;
+        let _oid = self.string()?;
+        let tag = self.string()?;
+        let desc = self.string()?;
+        let section = match self.int()? {
+            2 => "PRE_DATA",
+            3 => "DATA",
+            4 => "POST_DATA",
+            _ => "NONE",
+        };
+        let defn = self.string()?;
+        let _drop_stmt = self.string()?;
+        let _copy_stmt = self.string()?;
+        let namespace = self.string()?;
+        let _tablespace = self.string()?;
+        if version >= (1, 14) {
+            let _table_am = self.string()?;
+        }
+        if version >= (1, 16) {
+            let _relkind = self.int()?;
+        }
+        let owner = self.string()?;
+        let _with_oids = self.string()?;
+
+        // Dependency ids as decimal strings, terminated by NULL
+        let mut dependencies = Vec::new();
+        while let Some(dep) = self.optional_string()? {
+            let id = dep
+                .parse()
+                .map_err(|_| format!("invalid dependency id {:?} in entry {}", dep, dump_id))?;
+            dependencies.push(id);
+        }
+
+        // Custom-format data position: state flag plus offset
+        self.byte()?;
+        self.take(self.off_size)?;
+
+        Ok(DumpTocEntry {
+            dump_id,
+            desc,
+            tag,
+            namespace,
+            owner,
+            section: section.to_string(),
+            defn,
+            dependencies,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn parse(bytes: &[u8]) -> Result<DumpArchive, String> {
+        parse_archive(&mut ArchiveReader::new(bytes))
+    }
+
+    /// Minimal writer mirroring pg_dump's WriteInt/WriteStr encoding
+    struct ArchiveWriter {
+        bytes: Vec<u8>,
+    }
+
+    impl ArchiveWriter {
+        fn header(vmin: u8) -> Self {
+            let mut writer = Self { bytes: Vec::new() };
+            writer.bytes.extend_from_slice(b"PGDMP");
+            writer
+                .bytes
+                .extend_from_slice(&[1, vmin, 0, 4, 8, FORMAT_CUSTOM]);
+            if vmin >= 15 {
+                writer.bytes.push(0);
+            } else {
+                writer.int(0);
+            }
+            for value in [0, 30, 12, 1, 5, 124, 0] {
+                writer.int(value);
+            }
+            writer.string(Some("appdb"));
+            writer.string(Some("16.2"));
+            writer.string(Some("16.2"));
+            writer
+        }
+
+        fn int(&mut self, value: i64) {
+            self.bytes.push(u8::from(value < 0));
+            self.bytes
+                .extend_from_slice(&(value.unsigned_abs() as u32).to_le_bytes());
+        }
+
+        fn string(&mut self, value: Option<&str>) {
+            match value {
+                Some(s) => {
+                    self.int(s.len() as i64);
+                    self.bytes.extend_from_slice(s.as_bytes());
+                }
+                None => self.int(-1),
+            }
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        fn entry(
+            &mut self,
+            vmin: u8,
+            dump_id: i64,
+            desc: &str,
+            tag: &str,
+            section: i64,
+            defn: &str,
+            deps: &[i64],
+        ) {
+            self.int(dump_id);
+            self.int(0);
+            self.string(Some("0"));
+            self.string(Some("0"));
+            self.string(Some(tag));
+            self.string(Some(desc));
+            self.int(section);
+            self.string(Some(defn));
+            self.string(Some(""));
+            self.string(None);
+            self.string(Some("public"));
+            self.string(Some(""));
+            if vmin >= 14 {
+                self.string(Some("heap"));
+            }
+            if vmin >= 16 {
+                self.int(i64::from(b'r'));
+            }
+            self.string(Some("app"));
+            self.string(Some("false"));
+            for dep in deps {
+                self.string(Some(&dep.to_string()));
+            }
+            self.string(None);
+            self.bytes.push(1);
+            self.bytes.extend_from_slice(&[0; 8]);
+        }
+    }
+
+    fn sample_archive(vmin: u8) -> Vec<u8> {
+        let mut writer = ArchiveWriter::header(vmin);
+        writer.int(4);
+        writer.entry(
+            vmin,
+            3350,
+            "ENCODING",
+            "ENCODING",
+            2,
+            "SET client_encoding = 'UTF8';\n",
+            &[],
+        );
+        writer.entry(
+            vmin,
+            10,
+            "TABLE",
+            "users",
+            2,
+            "CREATE TABLE public.users (\n    id integer\n);\n",
+            &[],
+        );
+        writer.entry(vmin, 11, "TABLE DATA", "users", 3, "", &[10]);
+        writer.entry(
+            vmin,
+            12,
+            "INDEX",
+            "users_id_idx",
+            4,
+            "CREATE INDEX users_id_idx ON public.users USING btree (id);\n",
+            &[10],
+        );
+        writer.bytes
+    }
+
+    #[test]
+    fn test_parse_archive_reads_toc() {
+        for vmin in [12, 13, 14, 15, 16] {
+            let archive = parse(&sample_archive(vmin)).unwrap();
+
+            assert_eq!(archive.archive_version, format!("1.{}", vmin));
+            assert_eq!(archive.database, "appdb");
+            assert_eq!(archive.entries.len(), 4);
+            assert_eq!(archive.entries[1].desc, "TABLE");
+            assert_eq!(archive.entries[1].section, "PRE_DATA");
+            assert_eq!(archive.entries[3].tag, "users_id_idx");
+            assert_eq!(archive.entries[3].dependencies, vec![10]);
+            assert_eq!(archive.entries[3].owner, "app");
+        }
+    }
+
+    #[test]
+    fn test_schema_ddl_skips_data_and_session_entries() {
+        let archive = parse(&sample_archive(15)).unwrap();
+
+        let ddl = schema_ddl(&archive.entries);
+
+        assert!(ddl.starts_with("CREATE TABLE public.users"));
+        assert!(ddl.contains("CREATE INDEX users_id_idx"));
+        assert_eq!(ddl.matches("CREATE").count(), 2);
+    }
+
+    #[test]
+    fn test_parse_archive_rejects_plain_sql() {
+        let err = parse(b"-- PostgreSQL database dump\n").unwrap_err();
+
+        assert!(err.contains("PGDMP"));
+    }
+
+    #[test]
+    fn test_parse_archive_rejects_truncated_file() {
+        let bytes = sample_archive(15);
+
+        let err = parse(&bytes[..bytes.len() - 20]).unwrap_err();
+
+        assert!(err.contains("unexpected end of file"));
+    }
+
+    #[test]
+    fn test_reader_rejects_out_of_range_integers() {
+        // Negative sign with magnitude 2^63, then positive 2^63
+        let mut bytes = vec![1];
+        bytes.extend_from_slice(&(1u64 << 63).to_le_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(&(1u64 << 63).to_le_bytes());
+        let mut reader = ArchiveReader::new(bytes.as_slice());
+        reader.int_size = 8;
+
+        assert!(reader.int().unwrap_err().contains("integer out of range"));
+        let err = reader.int().unwrap_err();
+        assert_eq!(err, "integer out of range at byte 9");
+    }
+
+    #[test]
+    fn test_parse_archive_stops_after_toc() {
+        /// Stands in for data blocks that must never be read
+        struct Unreadable;
+
+        impl Read for Unreadable {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("read past the TOC"))
+            }
+        }
+
+        let bytes = sample_archive(16);
+        let mut reader = ArchiveReader::new(bytes.as_slice().chain(Unreadable));
+
+        let archive = parse_archive(&mut reader).unwrap();
+
+        assert_eq!(archive.entries.len(), 4);
+        assert_eq!(reader.pos, bytes.len() as u64);
+    }
+
+    #[test]
+    fn test_read_archive_maps_errors_to_codes() {
+        let temp_dir = TempDir::new().unwrap();
+        let dump_path = temp_dir.path().join("plain.dump");
+        fs::write(&dump_path, "SELECT 1;").unwrap();
+
+        let invalid = read_archive(&dump_path).unwrap_err();
+        let missing = read_archive(&temp_dir.path().join("missing.dump")).unwrap_err();
+
+        assert_eq!(invalid.code, ErrorCode::InvalidArchive);
+        assert_eq!(missing.code, ErrorCode::FileRead);
+    }
+}
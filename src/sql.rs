@@ -0,0 +1,388 @@
+//! Lightweight SQL text scanning
+//!
+//! Just enough lexing to tell SQL apart from whitespace and comments and to
+//! split a file into statements, without parsing them. Follows PostgreSQL's
+//! lexical rules: `--` runs to the end of the line, `/* ... */` block comments
+//! nest, and semicolons inside quoted strings, quoted identifiers and
+//! dollar-quoted bodies do not end a statement. Neither do semicolons inside
+//! the `BEGIN ATOMIC ... END` body of a SQL-standard `CREATE FUNCTION` or
+//! `CREATE PROCEDURE` (PostgreSQL 14+), tracked the way psql does.
+
+use crate::errors::{CoreError, ErrorCode};
+use std::path::Path;
+
+/// True if `sql` holds nothing but whitespace and comments
+///
+/// An unterminated block comment swallows the rest of the input, as it does
+/// for the server.
+pub fn is_blank(sql: &str) -> bool {
+    let bytes = sql.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b if b.is_ascii_whitespace() => i += 1,
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = bytes[i..]
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(bytes.len(), |offset| i + offset + 1);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => i = skip_block_comment(bytes, i),
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+/// One statement found by `split_statements`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    /// Statement text with comments dropped, whitespace outside literals
+    /// collapsed to single spaces, and no trailing `;`
+    pub text: String,
+    /// 1-based line the statement starts on
+    pub line: usize,
+    /// Byte offset in the input just past the terminating `;` (or the end of
+    /// the input for a final unterminated statement)
+    pub end: usize,
+}
+
+/// Split SQL text into normalized statements
+///
+/// Two statements that differ only in comments, indentation or line breaks
+/// normalize to the same `text`; literal contents are kept verbatim.
+pub fn split_statements(sql: &str) -> Vec<Statement> {
+    let bytes = sql.as_bytes();
+    let mut splitter = Splitter::default();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let end = match bytes[i] {
+            b';' if splitter.block_depth > 0 => {
+                splitter.push(";", line);
+                i + 1
+            }
+            b';' => {
+                splitter.finish(i + 1);
+                i + 1
+            }
+            b if b.is_ascii_whitespace() => {
+                splitter.pending_space = true;
+                i + 1
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                splitter.pending_space = true;
+                bytes[i..]
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(bytes.len(), |offset| i + offset)
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                splitter.pending_space = true;
+                skip_block_comment(bytes, i)
+            }
+            b'\'' => {
+                let end = skip_quoted(bytes, i, is_escape_string(bytes, i));
+                splitter.push(&sql[i..end], line);
+                end
+            }
+            b'"' => {
+                let end = skip_quoted(bytes, i, false);
+                splitter.push(&sql[i..end], line);
+                end
+            }
+            b'$' => {
+                let end = dollar_quote_end(bytes, i).unwrap_or(i + 1);
+                splitter.push(&sql[i..end], line);
+                end
+            }
+            b if is_ident_byte(b) => {
+                let end = i + bytes[i..]
+                    .iter()
+                    .position(|&b| !is_ident_byte(b) && b != b'$')
+                    .unwrap_or(bytes.len() - i);
+                splitter.word(&sql[i..end], line);
+                end
+            }
+            _ => {
+                let end = i + sql[i..].chars().next().map_or(1, char::len_utf8);
+                splitter.push(&sql[i..end], line);
+                end
+            }
+        };
+
+        line += bytes[i..end].iter().filter(|&&b| b == b'\n').count();
+        i = end;
+    }
+
+    splitter.finish(bytes.len());
+    splitter.statements
+}
+
+/// Accumulates normalized statement text for `split_statements`
+#[derive(Default)]
+struct Splitter {
+    statements: Vec<Statement>,
+    text: String,
+    line: usize,
+    pending_space: bool,
+    /// First words of the statement, lower-cased, to spot `CREATE FUNCTION`
+    leading_words: Vec<String>,
+    /// Open `BEGIN`/`CASE` blocks inside a routine body
+    block_depth: usize,
+}
+
+impl Splitter {
+    /// Append an identifier or keyword, tracking `BEGIN ATOMIC ... END`
+    fn word(&mut self, word: &str, line: usize) {
+        self.push(word, line);
+        if self.leading_words.len() < 4 {
+            self.leading_words.push(word.to_ascii_lowercase());
+        }
+        if !self.in_routine() {
+            return;
+        }
+        if word.eq_ignore_ascii_case("begin") || word.eq_ignore_ascii_case("case") {
+            self.block_depth += 1;
+        } else if word.eq_ignore_ascii_case("end") {
+            self.block_depth = self.block_depth.saturating_sub(1);
+        }
+    }
+
+    /// True once the statement reads `CREATE [OR REPLACE] FUNCTION` or
+    /// `PROCEDURE`
+    fn in_routine(&self) -> bool {
+        let words: Vec<&str> = self.leading_words.iter().map(String::as_str).collect();
+        matches!(
+            words.as_slice(),
+            ["create", "function" | "procedure", ..]
+                | ["create", "or", "replace", "function" | "procedure", ..]
+        )
+    }
+
+    /// Append a token, separated by one space if whitespace or a comment
+    /// preceded it
+    fn push(&mut self, token: &str, line: usize) {
+        if self.text.is_empty() {
+            self.line = line;
+        } else if self.pending_space {
+            self.text.push(' ');
+        }
+        self.pending_space = false;
+        self.text.push_str(token);
+    }
+
+    fn finish(&mut self, end: usize) {
+        if !self.text.is_empty() {
+            self.statements.push(Statement {
+                text: std::mem::take(&mut self.text),
+                line: self.line,
+                end,
+            });
+        }
+        self.pending_space = false;
+        self.leading_words.clear();
+        self.block_depth = 0;
+    }
+}
+
+pub(crate) fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+/// True if the quote at `quote` opens an `E'...'` string (backslash escapes)
+fn is_escape_string(bytes: &[u8], quote: usize) -> bool {
+    quote >= 1
+        && matches!(bytes[quote - 1], b'E' | b'e')
+        && (quote < 2 || !is_ident_byte(bytes[quote - 2]))
+}
+
+/// Index just past the quoted token starting at `start`; a doubled quote
+/// character is an escaped quote
+pub(crate) fn skip_quoted(bytes: &[u8], start: usize, backslash_escapes: bool) -> usize {
+    let quote = bytes[start];
+    let mut i = start + 1;
+
+    while i < bytes.len() {
+        if backslash_escapes && bytes[i] == b'\\' {
+            i += 2;
+        } else if bytes[i] == quote {
+            if bytes.get(i + 1) == Some(&quote) {
+                i += 2;
+            } else {
+                return i + 1;
+            }
+        } else {
+            i += 1;
+        }
+    }
+
+    bytes.len()
+}
+
+/// Index just past the dollar-quoted body starting at `start`, or `None` if
+/// the `$` does not open one (e.g. a `$1` parameter)
+pub(crate) fn dollar_quote_end(bytes: &[u8], start: usize) -> Option<usize> {
+    // `$` inside an identifier such as `a$b` is not a quote
+    if start > 0 && is_ident_byte(bytes[start - 1]) {
+        return None;
+    }
+
+    let mut tag_end = start + 1;
+    match bytes.get(tag_end) {
+        Some(b'$') => {}
+        Some(&b) if b.is_ascii_alphabetic() || b == b'_' || b >= 0x80 => {
+            while tag_end < bytes.len() && is_ident_byte(bytes[tag_end]) {
+                tag_end += 1;
+            }
+            if bytes.get(tag_end) != Some(&b'$') {
+                return None;
+            }
+        }
+        _ => return None,
+    }
+
+    let tag = &bytes[start..=tag_end];
+    let body = tag_end + 1;
+    Some(
+        bytes[body..]
+            .windows(tag.len())
+            .position(|window| window == tag)
+            .map_or(bytes.len(), |offset| body + offset + tag.len()),
+    )
+}
+
+/// Warning for a file that `is_blank`
+pub fn blank_file_notice(path: &Path) -> String {
+    format!("Schema file is empty or comment-only: {}", path.display())
+}
+
+/// Strict-mode error listing every blank file
+pub fn blank_files_error(paths: &[&Path]) -> CoreError {
+    let files: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    CoreError::new(
+        ErrorCode::EmptyFile,
+        format!(
+            "Schema files are empty or comment-only: {}",
+            files.join(", ")
+        ),
+    )
+    .with_detail("files", files.join("\n"))
+}
+
+/// Index just past the block comment starting at `start`, honouring nesting
+pub(crate) fn skip_block_comment(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1)) {
+            (b'/', Some(b'*')) => {
+                depth += 1;
+                i += 2;
+            }
+            (b'*', Some(b'/')) => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_blank_whitespace_and_comments() {
+        assert!(is_blank(""));
+        assert!(is_blank(" \n\t\r\n"));
+        assert!(is_blank("-- TODO: restore after merge\n\n-- another"));
+        assert!(is_blank(
+            "/* header */\n/* outer /* nested */ still comment */"
+        ));
+        assert!(is_blank("/* unterminated"));
+    }
+
+    fn texts(sql: &str) -> Vec<String> {
+        split_statements(sql).into_iter().map(|s| s.text).collect()
+    }
+
+    #[test]
+    fn test_split_statements_normalizes_layout() {
+        let sql =
+            "CREATE INDEX idx_a\n    ON t (a);  -- dup\n\n/* again */ CREATE  INDEX idx_a ON t (a)";
+
+        assert_eq!(
+            split_statements(sql),
+            vec![
+                Statement {
+                    text: "CREATE INDEX idx_a ON t (a)".to_string(),
+                    line: 1,
+                    end: 32,
+                },
+                Statement {
+                    text: "CREATE INDEX idx_a ON t (a)".to_string(),
+                    line: 4,
+                    end: sql.len(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_respects_quoting() {
+        let sql = "CREATE FUNCTION f() RETURNS int AS $body$\nBEGIN\n  RETURN 1; -- x\nEND;\n$body$ LANGUAGE plpgsql;\n\
+                   SELECT 'a;b', E'it\\'s;', \"semi;colon\", $1, a$b;\n\
+                   SELECT $$;$$;";
+
+        assert_eq!(
+            texts(sql),
+            vec![
+                "CREATE FUNCTION f() RETURNS int AS $body$\nBEGIN\n  RETURN 1; -- x\nEND;\n$body$ LANGUAGE plpgsql",
+                "SELECT 'a;b', E'it\\'s;', \"semi;colon\", $1, a$b",
+                "SELECT $$;$$",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_keeps_begin_atomic_bodies_whole() {
+        let sql = "CREATE OR REPLACE FUNCTION f(x int) RETURNS int LANGUAGE sql\n\
+                   BEGIN ATOMIC\n  SELECT CASE WHEN x > 0 THEN 1 ELSE 0 END;\n  SELECT 2;\nEND;\n\
+                   BEGIN;\nSELECT CASE WHEN true THEN 1 END;\nCOMMIT;";
+
+        assert_eq!(
+            texts(sql),
+            vec![
+                "CREATE OR REPLACE FUNCTION f(x int) RETURNS int LANGUAGE sql BEGIN ATOMIC SELECT CASE WHEN x > 0 THEN 1 ELSE 0 END; SELECT 2; END",
+                "BEGIN",
+                "SELECT CASE WHEN true THEN 1 END",
+                "COMMIT",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_skips_empty_statements() {
+        assert!(texts("  ;; -- only comments\n/* ; */").is_empty());
+    }
+
+    #[test]
+    fn test_is_blank_detects_sql() {
+        assert!(!is_blank("CREATE TABLE t (id INT);"));
+        assert!(!is_blank("-- header\nSELECT 1;"));
+        assert!(!is_blank("/* a /* b */ */ SELECT 1;"));
+        assert!(!is_blank("/* a /* b */ SELECT 1; */ x"));
+        assert!(!is_blank("- 1"));
+    }
+}
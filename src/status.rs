@@ -0,0 +1,379 @@
+//! Heartbeat status file for long-running operations
+//!
+//! While a build runs with a status path, the current phase, file, progress
+//! and ETA are written to that file (at most once a second, plus on every
+//! phase change), so supervisors and dashboards can follow progress without
+//! attaching to the process. A background timer rewrites it about once a
+//! second even while no file completes, so a stale `updated_at` means the
+//! process is gone, not that a read is slow. The file is replaced
+//! atomically (write to `<path>.tmp`, then rename) so readers never see a
+//! partial update.
+//!
+//! The format is one `key: value` per line:
+//!
+//! ```text
+//! operation: build
+//! phase: reading
+//! current: 10_tables/users.sql
+//! done: 12
+//! total: 340
+//! percent: 3.5
+//! elapsed_seconds: 1.2
+//! eta_seconds: 33.0
+//! updated_at: 1767225600
+//! pid: 4242
+//! error:
+//! ```
+
+#![allow(clippy::useless_conversion)]
+
+use crate::errors::{CoreError, ErrorCode};
+use crate::hasher::read_file;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Minimum time between two writes within the same phase
+const WRITE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the heartbeat timer checks whether a write is due
+const HEARTBEAT_TICK: Duration = Duration::from_millis(250);
+
+/// Snapshot of an operation's progress, as stored in a status file
+#[cfg_attr(feature = "python", pyclass(get_all, frozen))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationStatus {
+    /// Operation name, e.g. "build"
+    pub operation: String,
+    /// "reading", "done" or "failed"
+    pub phase: String,
+    /// File being processed, relative to the base directory
+    pub current: String,
+    /// Work units (files) completed
+    pub done: usize,
+    pub total: usize,
+    pub percent: f64,
+    pub elapsed_seconds: f64,
+    /// Estimated seconds remaining, unknown until a unit completes
+    pub eta_seconds: Option<f64>,
+    /// Unix time of this update
+    pub updated_at: u64,
+    /// Process writing the file
+    pub pid: u32,
+    /// Error message if the phase is "failed"
+    pub error: Option<String>,
+}
+
+/// Read the status file of a running or finished operation
+///
+/// Args:
+///     path: Status file passed as `status_path` to the operation
+///
+/// Returns:
+///     OperationStatus with the latest update
+///
+/// Raises:
+///     NativeError: code `SCHEMA_205` if the file cannot be read, or
+///         `SCHEMA_211` if it is not a status file
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn read_status(path: String) -> PyResult<OperationStatus> {
+    Ok(read_status_file(Path::new(&path))?)
+}
+
+/// Read and parse a status file (pure Rust core of `read_status`)
+pub fn read_status_file(path: &Path) -> Result<OperationStatus, CoreError> {
+    let bytes = read_file(path)?;
+
+    parse_status(&String::from_utf8_lossy(&bytes)).map_err(|reason| {
+        CoreError::new(
+            ErrorCode::InvalidStatus,
+            format!("Invalid status file {}: {}", path.display(), reason),
+        )
+        .with_detail("file", path.display().to_string())
+        .with_detail("reason", reason)
+    })
+}
+
+/// Throttled writer for one operation's status file
+///
+/// Safe to share between rayon workers.
+pub struct StatusFile {
+    path: PathBuf,
+    started: Instant,
+    state: Mutex<StatusState>,
+}
+
+struct StatusState {
+    status: OperationStatus,
+    last_write: Option<Instant>,
+    write_error: Option<String>,
+}
+
+impl StatusFile {
+    /// Start tracking `operation` over `total` units and write the first
+    /// update
+    pub fn start(path: &Path, operation: &str, total: usize) -> Self {
+        let status = OperationStatus {
+            operation: operation.to_string(),
+            phase: "reading".to_string(),
+            current: String::new(),
+            done: 0,
+            total,
+            percent: 0.0,
+            elapsed_seconds: 0.0,
+            eta_seconds: None,
+            updated_at: 0,
+            pid: std::process::id(),
+            error: None,
+        };
+        let status_file = Self {
+            path: path.to_path_buf(),
+            started: Instant::now(),
+            state: Mutex::new(StatusState {
+                status,
+                last_write: None,
+                write_error: None,
+            }),
+        };
+        status_file.update(|_| {}, true);
+        status_file
+    }
+
+    /// Record one completed unit
+    pub fn unit_done(&self, current: &str) {
+        self.update(
+            |status| {
+                status.done += 1;
+                status.current = current.to_string();
+            },
+            false,
+        );
+    }
+
+    /// Run `work` while a timer thread keeps the file fresh, so it is
+    /// rewritten about once a second even when no unit completes
+    pub fn heartbeat_during<R>(&self, work: impl FnOnce() -> R) -> R {
+        let (stop, stopped) = channel::<()>();
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(HEARTBEAT_TICK) {
+                    self.update(|_| {}, false);
+                }
+            });
+            let result = work();
+            // Disconnecting the channel ends the timer before the scope joins
+            drop(stop);
+            result
+        })
+    }
+
+    /// Mark the operation finished, successfully or not (always written)
+    pub fn finish(&self, error: Option<&CoreError>) {
+        self.update(
+            |status| match error {
+                Some(err) => {
+                    status.phase = "failed".to_string();
+                    status.error = Some(err.to_string());
+                }
+                None => {
+                    status.phase = "done".to_string();
+                    status.done = status.total;
+                }
+            },
+            true,
+        );
+    }
+
+    /// First failure to write the file, as a notice for the caller
+    pub fn write_error(&self) -> Option<String> {
+        self.lock().write_error.clone()
+    }
+
+    fn update(&self, change: impl FnOnce(&mut OperationStatus), force: bool) {
+        let mut state = self.lock();
+        change(&mut state.status);
+
+        let now = Instant::now();
+        if !force && state.last_write.is_some_and(|at| now - at < WRITE_INTERVAL) {
+            return;
+        }
+        state.last_write = Some(now);
+
+        let elapsed = (now - self.started).as_secs_f64();
+        let status = &mut state.status;
+        status.elapsed_seconds = elapsed;
+        status.percent = if status.total == 0 {
+            100.0
+        } else {
+            100.0 * status.done as f64 / status.total as f64
+        };
+        status.eta_seconds = (status.done > 0)
+            .then(|| elapsed / status.done as f64 * (status.total - status.done) as f64);
+        status.updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        let text = render_status(status);
+        if let Err(e) = write_atomically(&self.path, &text) {
+            state.write_error.get_or_insert_with(|| {
+                format!("Cannot write status file {}: {}", self.path.display(), e)
+            });
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StatusState> {
+        // A panic while holding the lock leaves plain data behind; keep going
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn write_atomically(path: &Path, text: &str) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, text)?;
+    fs::rename(&tmp, path)
+}
+
+fn render_status(status: &OperationStatus) -> String {
+    format!(
+        "operation: {}\nphase: {}\ncurrent: {}\ndone: {}\ntotal: {}\npercent: {:.1}\n\
+         elapsed_seconds: {:.1}\neta_seconds: {}\nupdated_at: {}\npid: {}\nerror: {}\n",
+        status.operation,
+        status.phase,
+        status.current,
+        status.done,
+        status.total,
+        status.percent,
+        status.elapsed_seconds,
+        status
+            .eta_seconds
+            .map_or(String::new(), |eta| format!("{:.1}", eta)),
+        status.updated_at,
+        status.pid,
+        // Keep the one-line-per-field format
+        status.error.as_deref().unwrap_or("").replace('\n', " "),
+    )
+}
+
+fn parse_status(text: &str) -> Result<OperationStatus, String> {
+    let field = |name: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .map(str::trim)
+            .ok_or_else(|| format!("missing '{}'", name))
+    };
+    fn number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+        value
+            .parse()
+            .map_err(|_| format!("invalid {} '{}'", name, value))
+    }
+    let optional = |value: &str| Some(value.to_string()).filter(|v| !v.is_empty());
+
+    Ok(OperationStatus {
+        operation: field("operation")?.to_string(),
+        phase: field("phase")?.to_string(),
+        current: field("current")?.to_string(),
+        done: number("done", field("done")?)?,
+        total: number("total", field("total")?)?,
+        percent: number("percent", field("percent")?)?,
+        elapsed_seconds: number("elapsed_seconds", field("elapsed_seconds")?)?,
+        eta_seconds: optional(field("eta_seconds")?)
+            .map(|eta| number("eta_seconds", &eta))
+            .transpose()?,
+        updated_at: number("updated_at", field("updated_at")?)?,
+        pid: number("pid", field("pid")?)?,
+        error: optional(field("error")?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_status_file_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("build.status");
+
+        let status = StatusFile::start(&path, "build", 4);
+        let first = read_status_file(&path).unwrap();
+        assert_eq!(first.phase, "reading");
+        assert_eq!(first.eta_seconds, None);
+        assert_eq!(first.pid, std::process::id());
+
+        // Throttled: a unit right after the first write is not flushed...
+        status.unit_done("10_tables/users.sql");
+        assert_eq!(read_status_file(&path).unwrap().done, 0);
+
+        // ...but completion always is
+        status.finish(None);
+        let done = read_status_file(&path).unwrap();
+        assert_eq!((done.phase.as_str(), done.done), ("done", 4));
+        assert_eq!(done.current, "10_tables/users.sql");
+        assert_eq!(done.percent, 100.0);
+        assert_eq!(done.eta_seconds, Some(0.0));
+        assert_eq!(status.write_error(), None);
+    }
+
+    #[test]
+    fn test_heartbeat_refreshes_while_work_stalls() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("build.status");
+
+        let status = StatusFile::start(&path, "build", 1);
+        let during = status.heartbeat_during(|| {
+            // One slow file: no unit completes for well over a second
+            thread::sleep(Duration::from_millis(1600));
+            read_status_file(&path).unwrap()
+        });
+
+        assert_eq!(during.done, 0);
+        assert!(during.elapsed_seconds >= 1.0, "{}", during.elapsed_seconds);
+    }
+
+    #[test]
+    fn test_status_file_records_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("build.status");
+
+        let status = StatusFile::start(&path, "build", 1);
+        status.finish(Some(&CoreError::new(
+            ErrorCode::EmptyFile,
+            "Schema files are empty",
+        )));
+
+        let failed = read_status_file(&path).unwrap();
+        assert_eq!(failed.phase, "failed");
+        assert_eq!(
+            failed.error.as_deref(),
+            Some("[SCHEMA_208] Schema files are empty")
+        );
+    }
+
+    #[test]
+    fn test_read_status_rejects_other_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("schema.sql");
+        fs::write(&path, "CREATE TABLE t (id INT);\n").unwrap();
+
+        let err = read_status_file(&path).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidStatus);
+    }
+
+    #[test]
+    fn test_status_write_error_is_reported() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("missing-dir").join("build.status");
+
+        let status = StatusFile::start(&path, "build", 1);
+
+        assert!(status.write_error().unwrap().contains("missing-dir"));
+    }
+}
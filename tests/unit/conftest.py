@@ -0,0 +1,124 @@
+"""Shared fixtures for the SchemaBuilder unit tests.
+
+``schema_builder`` lays out a throwaway project and ``fake_core`` replaces
+the Rust extension with a stand-in that delegates to the Python fallback,
+so the native code paths run without a compiled build.
+"""
+
+from __future__ import annotations
+
+import warnings
+from pathlib import Path
+
+import pytest
+
+from confiture.core import builder as builder_module
+from confiture.core.builder import SchemaBuilder
+
+DEFAULT_SCHEMA_FILES = {"01_users.sql": "CREATE TABLE users (id INT);\n"}
+
+
+@pytest.fixture
+def schema_builder(request, tmp_path) -> SchemaBuilder:
+    """Builder over ``db/schema/10_tables`` using the Rust-eligible style.
+
+    Parametrise indirectly with a ``{file name: content}`` dict to choose
+    the schema files; a single users table is used otherwise.
+    """
+    files = getattr(request, "param", DEFAULT_SCHEMA_FILES)
+    schema_dir = tmp_path / "db" / "schema" / "10_tables"
+    schema_dir.mkdir(parents=True)
+    for name, content in files.items():
+        (schema_dir / name).write_text(content)
+
+    config_dir = tmp_path / "db" / "environments"
+    config_dir.mkdir(parents=True)
+    (config_dir / "test.yaml").write_text(f"""
+name: test
+include_dirs:
+  - {tmp_path / "db" / "schema"}
+exclude_dirs: []
+database_url: postgresql://localhost/test
+""")
+
+    builder = SchemaBuilder(env="test", project_dir=tmp_path)
+    builder.env_config.build.separators.style = "line_comment"
+    return builder
+
+
+class FakeNativeError(Exception):
+    """Stand-in for confiture._core.NativeError."""
+
+    def __init__(self, message: str, code: str, details: dict[str, str]):
+        super().__init__(message)
+        self.code = code
+        self.details = details
+
+
+class FakeNativeWarning(UserWarning):
+    """Stand-in for confiture._core.NativeWarning."""
+
+
+class FakeCore:
+    """Stand-in for confiture._core delegating to the Python fallback.
+
+    ``warn`` is emitted as a NativeWarning on every call, ``fail`` raises a
+    SCHEMA_206 NativeError instead of returning, and ``corrupt_build`` /
+    ``corrupt_hash`` make the results diverge from the fallback. The strict
+    flag of the last call is kept in ``build_strict`` and ``hash_strict``.
+    """
+
+    NativeError = FakeNativeError
+    NativeWarning = FakeNativeWarning
+
+    def __init__(
+        self,
+        builder: SchemaBuilder,
+        warn: str | None = None,
+        fail: bool = False,
+        corrupt_build: str | None = None,
+        corrupt_hash: bool = False,
+    ):
+        self._builder = builder
+        self._warn = warn
+        self._fail = fail
+        self._corrupt_build = corrupt_build
+        self._corrupt_hash = corrupt_hash
+        self.build_strict: bool | None = None
+        self.hash_strict: bool | None = None
+
+    def build_schema(self, file_paths: list[str], _base_dir: str, strict: bool = False) -> str:
+        self.build_strict = strict
+        self._notify()
+        content = self._builder._build_python("", [Path(p) for p in file_paths])
+        if self._corrupt_build:
+            content = content.replace(self._corrupt_build, self._corrupt_build.upper())
+        return content
+
+    def hash_files(self, file_paths: list[str], _base_dir: str, strict: bool = False) -> str:
+        self.hash_strict = strict
+        self._notify()
+        digest = self._builder._compute_hash_python([Path(p) for p in file_paths])
+        return digest[::-1] if self._corrupt_hash else digest
+
+    def _notify(self) -> None:
+        if self._fail:
+            raise FakeNativeError("invalid option", "SCHEMA_206", {"option": "strict"})
+        if self._warn:
+            warnings.warn(self._warn, FakeNativeWarning, stacklevel=3)
+
+
+@pytest.fixture
+def fake_core(schema_builder, monkeypatch):
+    """Install a FakeCore over ``schema_builder`` as the Rust extension.
+
+    Returns a factory taking FakeCore's options and returning the fake.
+    """
+
+    def install(**options) -> FakeCore:
+        core = FakeCore(schema_builder, **options)
+        monkeypatch.setattr(builder_module, "HAS_RUST", True)
+        monkeypatch.setattr(builder_module, "_core", core)
+        return core
+
+    return install
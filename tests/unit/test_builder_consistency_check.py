@@ -0,0 +1,53 @@
+"""Tests for the Rust/Python consistency-check debug mode of SchemaBuilder.
+
+With ``CONFITURE_NATIVE_CONSISTENCY_CHECK`` set, every Rust fast-path call also
+runs the Python fallback and raises SchemaError on divergence. The Rust
+extension is replaced by the ``fake_core`` fixture so the tests run without
+a compiled build.
+"""
+
+import pytest
+
+from confiture.exceptions import SchemaError
+
+TWO_TABLES = {
+    "01_users.sql": "CREATE TABLE users (id INT);\n",
+    "02_posts.sql": "CREATE TABLE posts (id INT);\n",
+}
+
+
+@pytest.mark.parametrize("schema_builder", [TWO_TABLES], indirect=True)
+class TestConsistencyCheck:
+    """Divergence between the Rust and Python paths is reported."""
+
+    def test_matching_build_passes(self, schema_builder, fake_core, monkeypatch):
+        monkeypatch.setenv("CONFITURE_NATIVE_CONSISTENCY_CHECK", "1")
+        fake_core()
+
+        schema = schema_builder.build()
+
+        assert "CREATE TABLE posts" in schema
+
+    def test_divergent_build_names_file(self, schema_builder, fake_core, monkeypatch):
+        monkeypatch.setenv("CONFITURE_NATIVE_CONSISTENCY_CHECK", "1")
+        fake_core(corrupt_build="posts")
+
+        with pytest.raises(SchemaError, match="diverge") as exc_info:
+            schema_builder.build()
+
+        assert exc_info.value.context["file_path"].endswith("02_posts.sql")
+
+    def test_divergent_build_ignored_when_disabled(self, schema_builder, fake_core, monkeypatch):
+        monkeypatch.delenv("CONFITURE_NATIVE_CONSISTENCY_CHECK", raising=False)
+        fake_core(corrupt_build="posts")
+
+        schema = schema_builder.build()
+
+        assert "CREATE TABLE POSTS" in schema
+
+    def test_divergent_hash_raises(self, schema_builder, fake_core, monkeypatch):
+        monkeypatch.setenv("CONFITURE_NATIVE_CONSISTENCY_CHECK", "true")
+        fake_core(corrupt_hash=True)
+
+        with pytest.raises(SchemaError, match="hashes diverge"):
+            schema_builder.compute_hash()
@@ -0,0 +1,73 @@
+"""Tests for how SchemaBuilder treats NativeWarning and NativeError.
+
+Running with ``-W error::confiture._core.NativeWarning`` turns native
+warnings into exceptions; those must fail the build instead of silently
+falling back to the Python implementation. A NativeError fails it too, as
+a SchemaError carrying the native code and details. The Rust extension is
+replaced by the ``fake_core`` fixture so the tests run without a compiled
+build.
+"""
+
+import warnings
+
+import pytest
+
+from confiture.exceptions import SchemaError
+
+EMPTY_FILE = {
+    "01_users.sql": "CREATE TABLE users (id INT);\n",
+    "02_empty.sql": "",
+}
+EMPTY_FILE_WARNING = "Empty file: 02_empty.sql"
+
+
+@pytest.mark.parametrize("schema_builder", [EMPTY_FILE], indirect=True)
+class TestEscalatedNativeWarnings:
+    """A warning filter set to error fails the build instead of falling back."""
+
+    def test_build_raises(self, schema_builder, fake_core):
+        core = fake_core(warn=EMPTY_FILE_WARNING)
+
+        with warnings.catch_warnings():
+            warnings.simplefilter("error", core.NativeWarning)
+            with pytest.raises(core.NativeWarning, match="02_empty.sql"):
+                schema_builder.build()
+
+    def test_compute_hash_raises(self, schema_builder, fake_core):
+        core = fake_core(warn=EMPTY_FILE_WARNING)
+
+        with warnings.catch_warnings():
+            warnings.simplefilter("error", core.NativeWarning)
+            with pytest.raises(core.NativeWarning):
+                schema_builder.compute_hash()
+
+    def test_build_warns_by_default(self, schema_builder, fake_core):
+        core = fake_core(warn=EMPTY_FILE_WARNING)
+
+        with pytest.warns(core.NativeWarning, match="02_empty.sql"):
+            schema = schema_builder.build()
+
+        assert "CREATE TABLE users" in schema
+
+
+@pytest.mark.parametrize("schema_builder", [EMPTY_FILE], indirect=True)
+class TestNativeErrors:
+    """Errors raised by the extension surface as SchemaError, not a fallback."""
+
+    def test_build_raises(self, schema_builder, fake_core):
+        core = fake_core(fail=True)
+
+        with pytest.raises(SchemaError, match="invalid option") as exc_info:
+            schema_builder.build()
+
+        assert exc_info.value.error_code == "SCHEMA_206"
+        assert exc_info.value.context == {"option": "strict"}
+        assert isinstance(exc_info.value.__cause__, core.NativeError)
+
+    def test_compute_hash_raises(self, schema_builder, fake_core):
+        core = fake_core(fail=True)
+
+        with pytest.raises(SchemaError, match="invalid option") as exc_info:
+            schema_builder.compute_hash()
+
+        assert exc_info.value.error_code == "SCHEMA_206"
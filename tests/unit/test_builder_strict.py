@@ -0,0 +1,103 @@
+"""Tests for empty and comment-only file handling in SchemaBuilder.
+
+The Python fallback must warn about, or with ``strict=True`` reject, the same
+files the Rust extension does, so a project passes or fails the same way
+whether or not the extension is installed.
+"""
+
+import warnings
+
+import pytest
+
+from confiture.core import builder as builder_module
+from confiture.core.builder import _is_blank_sql
+from confiture.exceptions import SchemaError
+
+BLANK_FILES = {
+    "01_users.sql": "CREATE TABLE users (id INT);\n",
+    "02_empty.sql": "",
+    "03_comments.sql": "-- TODO\n/* restore after merge */\n",
+}
+
+
+@pytest.mark.parametrize("schema_builder", [BLANK_FILES], indirect=True)
+class TestPythonFallback:
+    """The fallback reports blank files like the Rust extension."""
+
+    def test_warns_for_each_blank_file(self, schema_builder, monkeypatch):
+        monkeypatch.setattr(builder_module, "HAS_RUST", False)
+
+        with warnings.catch_warnings(record=True) as caught:
+            warnings.simplefilter("always")
+            schema = schema_builder.build()
+
+        messages = [str(w.message) for w in caught]
+        assert "CREATE TABLE users" in schema
+        assert len(messages) == 2
+        assert messages[0].startswith("Schema file is empty or comment-only: ")
+        assert messages[0].endswith("02_empty.sql")
+        assert messages[1].endswith("03_comments.sql")
+
+    def test_strict_raises_schema_208(self, schema_builder, monkeypatch):
+        monkeypatch.setattr(builder_module, "HAS_RUST", False)
+
+        with pytest.raises(SchemaError) as exc_info:
+            schema_builder.build(strict=True)
+
+        assert exc_info.value.error_code == "SCHEMA_208"
+        assert len(exc_info.value.context["files"]) == 2
+
+    def test_compute_hash_warns_for_each_blank_file(self, schema_builder, monkeypatch):
+        monkeypatch.setattr(builder_module, "HAS_RUST", False)
+
+        with warnings.catch_warnings(record=True) as caught:
+            warnings.simplefilter("always")
+            schema_builder.compute_hash()
+
+        assert len(caught) == 2
+        assert str(caught[0].message).endswith("02_empty.sql")
+
+    def test_compute_hash_strict_raises_schema_208(self, schema_builder, monkeypatch):
+        monkeypatch.setattr(builder_module, "HAS_RUST", False)
+
+        with pytest.raises(SchemaError) as exc_info:
+            schema_builder.compute_hash(strict=True)
+
+        assert exc_info.value.error_code == "SCHEMA_208"
+
+
+@pytest.mark.parametrize("schema_builder", [BLANK_FILES], indirect=True)
+class TestStrictPassThrough:
+    """strict reaches the Rust extension."""
+
+    def test_strict_is_passed_to_core(self, schema_builder, fake_core):
+        core = fake_core()
+
+        schema_builder.build(strict=True)
+
+        assert core.build_strict is True
+
+    def test_compute_hash_strict_is_passed_to_core(self, schema_builder, fake_core):
+        core = fake_core()
+
+        schema_builder.compute_hash(strict=True)
+
+        assert core.hash_strict is True
+
+
+class TestIsBlankSql:
+    """_is_blank_sql matches the Rust lexer's notion of blank."""
+
+    @pytest.mark.parametrize(
+        "sql",
+        ["", " \n\t", "-- a\n-- b", "/* a /* b */ c */", "/* unterminated"],
+    )
+    def test_blank(self, sql):
+        assert _is_blank_sql(sql)
+
+    @pytest.mark.parametrize(
+        "sql",
+        ["SELECT 1;", "-- header\nSELECT 1;", "/* a /* b */ SELECT 1; */ x", "- 1"],
+    )
+    def test_not_blank(self, sql):
+        assert not _is_blank_sql(sql)